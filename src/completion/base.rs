@@ -24,6 +24,31 @@ impl Span {
 
         Span { start, end }
     }
+
+    /// Moves the start of the span to the start of the whitespace separated word it is in
+    ///
+    /// Completers can use it to replace the complete word in front of the cursor.
+    ///
+    /// # Example
+    /// ```
+    /// use reedline::Span;
+    ///
+    /// let line = "git chec";
+    /// assert_eq!(Span::new(8, 8).grow_left_to_word_start(line), Span::new(4, 8));
+    /// ```
+    #[must_use]
+    pub fn grow_left_to_word_start(self, line: &str) -> Span {
+        let start = line[..self.start]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(index, c)| index + c.len_utf8());
+
+        Span {
+            start,
+            end: self.end,
+        }
+    }
 }
 
 /// A trait that defines how to convert a line and position to a list of potential completions in that position.
@@ -0,0 +1,207 @@
+use crate::{Completer, Span, Suggestion};
+
+/// Result of matching a typed pattern as a subsequence of a candidate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// Higher scores are better matches
+    pub score: i64,
+    /// Byte positions of the matched characters in the candidate, e.g. for highlighting
+    pub positions: Vec<usize>,
+}
+
+impl FuzzyMatch {
+    /// Matches `pattern` against `candidate` ignoring case
+    ///
+    /// Returns `None` if the characters of `pattern` don't appear in order in `candidate`.
+    /// Consecutive matches and matches at the start of a word score higher, gaps lower the score.
+    pub fn find(pattern: &str, candidate: &str) -> Option<FuzzyMatch> {
+        let mut pattern_chars = pattern.chars().peekable();
+        let mut positions = Vec::new();
+        let mut score = 0;
+        let mut previous: Option<(usize, char)> = None;
+        let mut last_match: Option<usize> = None;
+
+        for (index, c) in candidate.char_indices() {
+            let pattern_char = match pattern_chars.peek() {
+                Some(pattern_char) => *pattern_char,
+                None => break,
+            };
+
+            if c.to_lowercase().eq(pattern_char.to_lowercase()) {
+                score += 16;
+                match (last_match, previous) {
+                    (Some(last), Some((previous_index, _))) if last == previous_index => {
+                        score += 15
+                    }
+                    _ => {}
+                }
+                if !matches!(previous, Some((_, previous_char)) if previous_char.is_alphanumeric())
+                {
+                    score += 10;
+                }
+
+                positions.push(index);
+                last_match = Some(index);
+                pattern_chars.next();
+            } else {
+                // Every skipped character lowers the score
+                score -= 1;
+            }
+
+            previous = Some((index, c));
+        }
+
+        if pattern_chars.peek().is_some() {
+            None
+        } else {
+            Some(FuzzyMatch { score, positions })
+        }
+    }
+}
+
+/// Adapter that adds fuzzy matching to any [`Completer`]
+///
+/// The wrapped completer is asked for the candidates starting with the first character of
+/// the word in front of the cursor. These are filtered and ordered by how well the complete
+/// word matches as a subsequence ([`FuzzyMatch`]). The spans of the suggestions are adjusted
+/// to replace the complete word.
+///
+/// # Example
+/// ```rust,no_run
+/// use reedline::{DefaultCompleter, FuzzyCompleter, Reedline};
+///
+/// let commands = vec!["checkout".into(), "cherry-pick".into(), "commit".into()];
+/// let completer = Box::new(FuzzyCompleter::new(Box::new(DefaultCompleter::new(commands))));
+///
+/// let mut line_editor = Reedline::create().with_completer(completer);
+/// ```
+pub struct FuzzyCompleter {
+    completer: Box<dyn Completer>,
+}
+
+impl FuzzyCompleter {
+    /// Wraps `completer` to match its suggestions fuzzily
+    pub fn new(completer: Box<dyn Completer>) -> Self {
+        Self { completer }
+    }
+
+    /// Suggestions like [`Completer::complete`] with their matches, e.g. to highlight the
+    /// matched characters
+    ///
+    /// The positions of the matches are byte positions in the values of the suggestions.
+    pub fn complete_with_positions(&self, line: &str, pos: usize) -> Vec<(Suggestion, FuzzyMatch)> {
+        let word_start = Span::new(pos, pos).grow_left_to_word_start(line).start;
+        let typed = &line[word_start..pos];
+
+        // Only the first character is passed on to collect all candidates for the word
+        let anchor = typed
+            .chars()
+            .next()
+            .map_or(word_start, |c| word_start + c.len_utf8());
+        let removed = pos - anchor;
+        let query_line = format!("{}{}", &line[..anchor], &line[pos..]);
+
+        let mut matches: Vec<(Suggestion, FuzzyMatch)> = self
+            .completer
+            .complete(&query_line, anchor)
+            .into_iter()
+            .filter_map(|mut suggestion| {
+                let fuzzy_match = FuzzyMatch::find(typed, &suggestion.value)?;
+                if suggestion.span.start > anchor {
+                    suggestion.span.start += removed;
+                }
+                if suggestion.span.end >= anchor {
+                    suggestion.span.end += removed;
+                }
+                Some((suggestion, fuzzy_match))
+            })
+            .collect();
+
+        matches.sort_by_key(|(_, fuzzy_match)| std::cmp::Reverse(fuzzy_match.score));
+        matches
+    }
+}
+
+impl Completer for FuzzyCompleter {
+    fn complete(&self, line: &str, pos: usize) -> Vec<Suggestion> {
+        self.complete_with_positions(line, pos)
+            .into_iter()
+            .map(|(suggestion, _)| suggestion)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DefaultCompleter;
+    use pretty_assertions::assert_eq;
+
+    fn completer() -> FuzzyCompleter {
+        let commands = ["checkout", "chkconfig", "commit", "clone", "branch"];
+        FuzzyCompleter::new(Box::new(DefaultCompleter::new(
+            commands.iter().map(|command| command.to_string()).collect(),
+        )))
+    }
+
+    #[test]
+    fn subsequence_matches_are_ordered_by_score() {
+        let suggestions = completer().complete("git chk", 7);
+
+        assert_eq!(
+            suggestions,
+            vec![
+                Suggestion {
+                    value: "chkconfig".into(),
+                    description: None,
+                    span: Span::new(4, 7),
+                },
+                Suggestion {
+                    value: "checkout".into(),
+                    description: None,
+                    span: Span::new(4, 7),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn non_contiguous_match_is_found() {
+        let values: Vec<String> = completer()
+            .complete("git cmt", 7)
+            .into_iter()
+            .map(|suggestion| suggestion.value)
+            .collect();
+
+        assert_eq!(values, vec!["commit".to_string()]);
+    }
+
+    #[test]
+    fn match_positions_point_at_matched_characters() {
+        let fuzzy_match = FuzzyMatch::find("chk", "checkout").unwrap();
+
+        assert_eq!(fuzzy_match.positions, vec![0, 1, 4]);
+        assert!(FuzzyMatch::find("chk", "commit").is_none());
+        assert!(
+            FuzzyMatch::find("cp", "cherry-pick").unwrap().score
+                > FuzzyMatch::find("cp", "cheapo").unwrap().score
+        );
+    }
+
+    #[test]
+    fn suggestions_come_with_their_match_positions() {
+        let matches: Vec<(String, Vec<usize>)> = completer()
+            .complete_with_positions("git chk", 7)
+            .into_iter()
+            .map(|(suggestion, fuzzy_match)| (suggestion.value, fuzzy_match.positions))
+            .collect();
+
+        assert_eq!(
+            matches,
+            vec![
+                ("chkconfig".to_string(), vec![0, 1, 2]),
+                ("checkout".to_string(), vec![0, 1, 4]),
+            ]
+        );
+    }
+}
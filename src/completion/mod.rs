@@ -1,7 +1,9 @@
 mod base;
 mod circular;
 mod default;
+mod fuzzy;
 
 pub use base::{Completer, Span, Suggestion};
 pub use circular::CircularCompletionHandler;
 pub use default::DefaultCompleter;
+pub use fuzzy::{FuzzyCompleter, FuzzyMatch};
@@ -1,3 +1,5 @@
+use crate::utils::text_manipulation;
+
 /// Defines an interface to interact with a Clipboard for cut and paste.
 ///
 /// Mutable reference requirements are stricter than always necessary, but the currently used system clipboard API demands them for exclusive access.
@@ -42,6 +44,11 @@ impl LocalClipboard {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Overwrites the content before clearing it
+    pub fn zeroize(&mut self) {
+        text_manipulation::zeroize(&mut self.content);
+    }
 }
 
 impl Clipboard for LocalClipboard {
@@ -85,33 +92,54 @@ mod system_clipboard {
 
     /// Wrapper around [`clipboard`](https://docs.rs/clipboard) crate
     ///
-    /// Requires that the feature `system_clipboard` is enabled
+    /// Requires that the feature `system_clipboard` is enabled.
+    /// If the system clipboard can't be accessed (e.g. no display is available)
+    /// or reading it fails, the content is only kept inside the line editor.
+    ///
+    /// The `clipboard` crate was already the backend of this feature, so it is kept
+    /// instead of moving to `arboard`, which would change the feature's dependencies.
     pub struct SystemClipboard {
-        cb: ClipboardContext,
+        cb: Option<ClipboardContext>,
         local_copy: String,
         mode: ClipboardMode,
     }
 
     impl SystemClipboard {
         pub fn new() -> Self {
-            let cb = ClipboardProvider::new().unwrap();
+            let cb = ClipboardProvider::new().ok();
             SystemClipboard {
                 cb,
                 local_copy: String::new(),
                 mode: ClipboardMode::Normal,
             }
         }
+
+        /// Clipboard behaving as if no system clipboard is available
+        #[cfg(test)]
+        pub(super) fn without_system_clipboard() -> Self {
+            SystemClipboard {
+                cb: None,
+                local_copy: String::new(),
+                mode: ClipboardMode::Normal,
+            }
+        }
     }
 
     impl Clipboard for SystemClipboard {
         fn set(&mut self, content: &str, mode: ClipboardMode) {
             self.local_copy = content.to_owned();
-            let _ = self.cb.set_contents(content.to_owned());
+            if let Some(cb) = self.cb.as_mut() {
+                let _ = cb.set_contents(content.to_owned());
+            }
             self.mode = mode;
         }
 
         fn get(&mut self) -> (String, ClipboardMode) {
-            let system_content = self.cb.get_contents().unwrap_or_default();
+            let system_content = self
+                .cb
+                .as_mut()
+                .and_then(|cb| cb.get_contents().ok())
+                .unwrap_or_else(|| self.local_copy.clone());
             if system_content == self.local_copy {
                 // We assume the content was yanked inside the line editor and the last yank determined the mode.
                 (system_content, self.mode)
@@ -143,4 +171,35 @@ mod tests {
 
         cb.set(&previous_state, ClipboardMode::Normal);
     }
+
+    #[cfg(feature = "system_clipboard")]
+    #[test]
+    fn system_clipboard_keeps_mode_of_own_cuts() {
+        use super::SystemClipboard;
+
+        let mut cb = SystemClipboard::new();
+        let previous_state = cb.get().0;
+
+        cb.set("line\n", ClipboardMode::Lines);
+        assert_eq!(cb.len(), 5);
+        assert!(matches!(cb.get(), (content, ClipboardMode::Lines) if content == "line\n"));
+        cb.clear();
+        assert!(matches!(cb.get(), (content, ClipboardMode::Normal) if content.is_empty()));
+
+        cb.set(&previous_state, ClipboardMode::Normal);
+    }
+
+    #[cfg(feature = "system_clipboard")]
+    #[test]
+    fn system_clipboard_falls_back_to_local_copy() {
+        use super::SystemClipboard;
+
+        let mut cb = SystemClipboard::without_system_clipboard();
+
+        cb.set("line\n", ClipboardMode::Lines);
+        assert_eq!(cb.len(), 5);
+        assert!(matches!(cb.get(), (content, ClipboardMode::Lines) if content == "line\n"));
+        cb.clear();
+        assert!(matches!(cb.get(), (content, ClipboardMode::Normal) if content.is_empty()));
+    }
 }
@@ -51,6 +51,11 @@ where
         self.index += 1;
     }
 
+    /// Runs `f` on every entry, e.g. to wipe them before a reset
+    pub(super) fn for_each_mut(&mut self, f: impl FnMut(&mut T)) {
+        self.internal_list.iter_mut().for_each(f);
+    }
+
     /// Reset the stack to the initial state
     pub(super) fn reset(&mut self) {
         self.index = 0;
@@ -61,6 +66,18 @@ where
     pub(super) fn current(&mut self) -> &T {
         &self.internal_list[self.index]
     }
+
+    /// Number of entries in the undo stack, including the ones that can be redone
+    pub(super) fn len(&self) -> usize {
+        self.internal_list.len()
+    }
+
+    /// Return the entry an undo would go back to without moving
+    pub(super) fn peek_undo(&self) -> Option<&T> {
+        self.index
+            .checked_sub(1)
+            .and_then(|index| self.internal_list.get(index))
+    }
 }
 
 #[cfg(test)]
@@ -89,6 +106,15 @@ mod test {
         assert_eq!(*value, value_after_undo);
     }
 
+    #[rstest]
+    #[case(edit_stack(&[1, 2, 3][..], 2), Some(2))]
+    #[case(edit_stack(&[1, 2, 3][..], 1), Some(1))]
+    #[case(edit_stack(&[1][..], 0), None)]
+    fn peek_undo_works(#[case] stack: EditStack<isize>, #[case] expected: Option<isize>) {
+        assert_eq!(stack.peek_undo().copied(), expected);
+        assert_eq!(stack.len(), stack.internal_list.len());
+    }
+
     #[rstest]
     #[case(edit_stack(&[1, 2, 3][..], 1), 3)]
     #[case(edit_stack(&[1][..], 0), 1)]
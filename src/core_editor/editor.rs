@@ -1,5 +1,9 @@
-use super::{edit_stack::EditStack, Clipboard, ClipboardMode, LineBuffer};
+use super::{
+    clip_buffer::LocalClipboard, edit_stack::EditStack, kill_ring::KillRing, Clipboard,
+    ClipboardMode, LineBuffer, WordBoundary,
+};
 use crate::{core_editor::get_default_clipboard, EditCommand, UndoBehavior};
+use std::ops::Range;
 
 /// Stateful editor executing changes to the underlying [`LineBuffer`]
 ///
@@ -7,17 +11,30 @@ use crate::{core_editor::get_default_clipboard, EditCommand, UndoBehavior};
 /// the undo/redo history and has facilities for cut/copy/yank/paste
 pub struct Editor {
     line_buffer: LineBuffer,
+    word_boundary: WordBoundary,
     cut_buffer: Box<dyn Clipboard>,
+    // Takes the place of `cut_buffer` while a secret is edited
+    secret_cut_buffer: Option<LocalClipboard>,
+    kill_ring: KillRing,
+    last_edit_was_kill: bool,
+    last_paste: Option<Range<usize>>,
 
     edit_stack: EditStack<LineBuffer>,
+    coalescing_edits: bool,
 }
 
 impl Default for Editor {
     fn default() -> Self {
         Editor {
             line_buffer: LineBuffer::new(),
+            word_boundary: WordBoundary::default(),
             cut_buffer: Box::new(get_default_clipboard()),
+            secret_cut_buffer: None,
+            kill_ring: KillRing::default(),
+            last_edit_was_kill: false,
+            last_paste: None,
             edit_stack: EditStack::new(),
+            coalescing_edits: false,
         }
     }
 }
@@ -31,7 +48,22 @@ impl Editor {
         self.line_buffer = line_buffer;
     }
 
+    /// Sets what is considered a word by the word-wise edit commands
+    pub fn set_word_boundary(&mut self, word_boundary: WordBoundary) {
+        self.word_boundary = word_boundary;
+    }
+
+    /// Sets the number of cuts kept in the kill ring
+    ///
+    /// With more than one entry consecutive cuts are accumulated into the same
+    /// entry and [`EditCommand::YankPop`] can cycle through older cuts.
+    pub fn set_kill_ring_size(&mut self, size: usize) {
+        self.kill_ring.resize(size);
+    }
+
     pub fn run_edit_command(&mut self, command: &EditCommand) {
+        // Only a paste directly before a yank pop can be replaced
+        let last_paste = self.last_paste.take();
         match command {
             EditCommand::MoveToStart => self.line_buffer.move_to_start(),
             EditCommand::MoveToLineStart => self.line_buffer.move_to_line_start(),
@@ -40,15 +72,15 @@ impl Editor {
             EditCommand::MoveToPosition(pos) => self.line_buffer.set_insertion_point(*pos),
             EditCommand::MoveLeft => self.line_buffer.move_left(),
             EditCommand::MoveRight => self.line_buffer.move_right(),
-            EditCommand::MoveWordLeft => self.line_buffer.move_word_left(),
-            EditCommand::MoveWordRight => self.line_buffer.move_word_right(),
+            EditCommand::MoveWordLeft => self.line_buffer.move_word_left(&self.word_boundary),
+            EditCommand::MoveWordRight => self.line_buffer.move_word_right(&self.word_boundary),
             EditCommand::InsertChar(c) => self.insert_char(*c),
             EditCommand::InsertString(str) => self.line_buffer.insert_str(str),
             EditCommand::ReplaceChars(n_chars, str) => self.replace_chars(*n_chars, str),
             EditCommand::Backspace => self.line_buffer.delete_left_grapheme(),
             EditCommand::Delete => self.line_buffer.delete_right_grapheme(),
-            EditCommand::BackspaceWord => self.line_buffer.delete_word_left(),
-            EditCommand::DeleteWord => self.line_buffer.delete_word_right(),
+            EditCommand::BackspaceWord => self.line_buffer.delete_word_left(&self.word_boundary),
+            EditCommand::DeleteWord => self.line_buffer.delete_word_right(&self.word_boundary),
             EditCommand::Clear => self.line_buffer.clear(),
             EditCommand::ClearToLineEnd => self.line_buffer.clear_to_line_end(),
             EditCommand::CutCurrentLine => self.cut_current_line(),
@@ -60,10 +92,11 @@ impl Editor {
             EditCommand::CutWordRight => self.cut_word_right(),
             EditCommand::PasteCutBufferBefore => self.insert_cut_buffer_before(),
             EditCommand::PasteCutBufferAfter => self.insert_cut_buffer_after(),
-            EditCommand::UppercaseWord => self.line_buffer.uppercase_word(),
-            EditCommand::LowercaseWord => self.line_buffer.lowercase_word(),
-            EditCommand::CapitalizeChar => self.line_buffer.capitalize_char(),
-            EditCommand::SwapWords => self.line_buffer.swap_words(),
+            EditCommand::YankPop => self.yank_pop(last_paste),
+            EditCommand::UppercaseWord => self.line_buffer.uppercase_word(&self.word_boundary),
+            EditCommand::LowercaseWord => self.line_buffer.lowercase_word(&self.word_boundary),
+            EditCommand::CapitalizeChar => self.line_buffer.capitalize_char(&self.word_boundary),
+            EditCommand::SwapWords => self.line_buffer.swap_words(&self.word_boundary),
             EditCommand::SwapGraphemes => self.line_buffer.swap_graphemes(),
             EditCommand::Undo => self.undo(),
             EditCommand::Redo => self.redo(),
@@ -76,13 +109,18 @@ impl Editor {
             EditCommand::MoveLeftUntil(c) => self.move_left_until_char(*c, false, true),
             EditCommand::MoveLeftBefore(c) => self.move_left_until_char(*c, true, true),
         }
+        self.last_edit_was_kill = is_kill(command);
         match command.undo_behavior() {
-            UndoBehavior::Ignore => {}
+            UndoBehavior::Ignore => {
+                self.coalescing_edits = false;
+            }
             UndoBehavior::Full => {
                 self.remember_undo_state(true);
             }
             UndoBehavior::Coalesce => {
-                self.remember_undo_state(false);
+                // Only a run of coalescing edits is grouped, the first one starts a new undo step
+                self.remember_undo_state(!self.coalescing_edits);
+                self.coalescing_edits = true;
             }
         }
     }
@@ -150,8 +188,50 @@ impl Editor {
         self.line_buffer.insertion_point() == self.get_buffer().len()
     }
 
+    /// Number of states in the undo stack, including the ones that can be redone
+    pub fn undo_stack_len(&self) -> usize {
+        self.edit_stack.len()
+    }
+
+    /// State of the line buffer an undo would restore, if there is one
+    pub fn peek_undo(&self) -> Option<&LineBuffer> {
+        self.edit_stack.peek_undo()
+    }
+
     pub fn reset_undo_stack(&mut self) {
         self.edit_stack.reset();
+        self.coalescing_edits = false;
+    }
+
+    /// Keeps cuts in a private clipboard until the next [`Editor::zeroize()`]
+    ///
+    /// Used for secret input, which must neither reach the system clipboard nor be
+    /// pasted once the secret has been read.
+    pub(crate) fn isolate_cut_buffer(&mut self) {
+        self.secret_cut_buffer
+            .get_or_insert_with(LocalClipboard::new);
+    }
+
+    /// Clears the buffer, its undo states and the kill ring, overwriting their content first
+    ///
+    /// The private clipboard set up by [`Editor::isolate_cut_buffer()`] is overwritten
+    /// and dropped, the regular clipboard used for cut and paste is left untouched.
+    pub(crate) fn zeroize(&mut self) {
+        self.line_buffer.zeroize();
+        self.edit_stack.for_each_mut(LineBuffer::zeroize);
+        self.reset_undo_stack();
+        self.kill_ring.zeroize();
+        if let Some(mut secret_cut_buffer) = self.secret_cut_buffer.take() {
+            secret_cut_buffer.zeroize();
+        }
+        self.last_paste = None;
+    }
+
+    fn cut_buffer(&mut self) -> &mut dyn Clipboard {
+        match &mut self.secret_cut_buffer {
+            Some(secret_cut_buffer) => secret_cut_buffer,
+            None => self.cut_buffer.as_mut(),
+        }
     }
 
     pub fn move_to_start(&mut self) {
@@ -182,6 +262,9 @@ impl Editor {
     }
 
     pub fn remember_undo_state(&mut self, is_after_action: bool) -> Option<()> {
+        if is_after_action {
+            self.coalescing_edits = false;
+        }
         if self.edit_stack.current().word_count() == self.line_buffer.word_count()
             && !is_after_action
         {
@@ -192,12 +275,33 @@ impl Editor {
         Some(())
     }
 
+    /// Stores a cut in the cut buffer and the kill ring
+    ///
+    /// If the kill ring holds more than one entry, a cut directly following
+    /// another cut extends the previous entry like emacs does.
+    fn kill(&mut self, content: &str, mode: ClipboardMode, prepend: bool) {
+        let accumulate = self.last_edit_was_kill
+            && self.kill_ring.capacity() > 1
+            && matches!(mode, ClipboardMode::Normal);
+        if accumulate {
+            self.kill_ring.append(content, prepend);
+        } else {
+            self.kill_ring.push(content);
+        }
+        if let Some(latest) = self.kill_ring.latest() {
+            match &mut self.secret_cut_buffer {
+                Some(secret_cut_buffer) => secret_cut_buffer.set(latest, mode),
+                None => self.cut_buffer.set(latest, mode),
+            }
+        }
+    }
+
     fn cut_current_line(&mut self) {
         let deletion_range = self.line_buffer.current_line_range();
 
-        let cut_slice = &self.line_buffer.get_buffer()[deletion_range.clone()];
+        let cut_slice = self.line_buffer.get_buffer()[deletion_range.clone()].to_string();
         if !cut_slice.is_empty() {
-            self.cut_buffer.set(cut_slice, ClipboardMode::Lines);
+            self.kill(&cut_slice, ClipboardMode::Lines, false);
             self.set_insertion_point(deletion_range.start);
             self.clear_range(deletion_range);
         }
@@ -206,10 +310,8 @@ impl Editor {
     fn cut_from_start(&mut self) {
         let insertion_offset = self.line_buffer.insertion_point();
         if insertion_offset > 0 {
-            self.cut_buffer.set(
-                &self.line_buffer.get_buffer()[..insertion_offset],
-                ClipboardMode::Normal,
-            );
+            let cut_slice = self.line_buffer.get_buffer()[..insertion_offset].to_string();
+            self.kill(&cut_slice, ClipboardMode::Normal, true);
             self.clear_to_insertion_point();
         }
     }
@@ -218,39 +320,39 @@ impl Editor {
         let previous_offset = self.line_buffer.insertion_point();
         self.line_buffer.move_to_line_start();
         let deletion_range = self.line_buffer.insertion_point()..previous_offset;
-        let cut_slice = &self.line_buffer.get_buffer()[deletion_range.clone()];
+        let cut_slice = self.line_buffer.get_buffer()[deletion_range.clone()].to_string();
         if !cut_slice.is_empty() {
-            self.cut_buffer.set(cut_slice, ClipboardMode::Normal);
+            self.kill(&cut_slice, ClipboardMode::Normal, true);
             self.line_buffer.clear_range(deletion_range);
         }
     }
 
     pub fn cut_from_end(&mut self) {
-        let cut_slice = &self.line_buffer.get_buffer()[self.line_buffer.insertion_point()..];
+        let cut_slice =
+            self.line_buffer.get_buffer()[self.line_buffer.insertion_point()..].to_string();
         if !cut_slice.is_empty() {
-            self.cut_buffer.set(cut_slice, ClipboardMode::Normal);
+            self.kill(&cut_slice, ClipboardMode::Normal, false);
             self.clear_to_end();
         }
     }
 
     fn cut_to_line_end(&mut self) {
-        let cut_slice = &self.line_buffer.get_buffer()
-            [self.line_buffer.insertion_point()..self.line_buffer.find_current_line_end()];
+        let cut_slice = self.line_buffer.get_buffer()
+            [self.line_buffer.insertion_point()..self.line_buffer.find_current_line_end()]
+            .to_string();
         if !cut_slice.is_empty() {
-            self.cut_buffer.set(cut_slice, ClipboardMode::Normal);
+            self.kill(&cut_slice, ClipboardMode::Normal, false);
             self.line_buffer.clear_to_line_end();
         }
     }
 
     fn cut_word_left(&mut self) {
         let insertion_offset = self.line_buffer.insertion_point();
-        let left_index = self.line_buffer.word_left_index();
+        let left_index = self.line_buffer.word_left_index(&self.word_boundary);
         if left_index < insertion_offset {
             let cut_range = left_index..insertion_offset;
-            self.cut_buffer.set(
-                &self.line_buffer.get_buffer()[cut_range.clone()],
-                ClipboardMode::Normal,
-            );
+            let cut_slice = self.line_buffer.get_buffer()[cut_range.clone()].to_string();
+            self.kill(&cut_slice, ClipboardMode::Normal, true);
             self.clear_range(cut_range);
             self.line_buffer.set_insertion_point(left_index);
         }
@@ -258,21 +360,22 @@ impl Editor {
 
     fn cut_word_right(&mut self) {
         let insertion_offset = self.line_buffer.insertion_point();
-        let right_index = self.line_buffer.word_right_index();
+        let right_index = self.line_buffer.word_right_index(&self.word_boundary);
         if right_index > insertion_offset {
             let cut_range = insertion_offset..right_index;
-            self.cut_buffer.set(
-                &self.line_buffer.get_buffer()[cut_range.clone()],
-                ClipboardMode::Normal,
-            );
+            let cut_slice = self.line_buffer.get_buffer()[cut_range.clone()].to_string();
+            self.kill(&cut_slice, ClipboardMode::Normal, false);
             self.clear_range(cut_range);
         }
     }
 
     fn insert_cut_buffer_before(&mut self) {
-        match self.cut_buffer.get() {
+        self.kill_ring.reset_yank();
+        match self.cut_buffer().get() {
             (content, ClipboardMode::Normal) => {
+                let start = self.line_buffer.insertion_point();
                 self.line_buffer.insert_str(&content);
+                self.last_paste = Some(start..self.line_buffer.insertion_point());
             }
             (mut content, ClipboardMode::Lines) => {
                 // TODO: Simplify that?
@@ -288,10 +391,13 @@ impl Editor {
     }
 
     fn insert_cut_buffer_after(&mut self) {
-        match self.cut_buffer.get() {
+        self.kill_ring.reset_yank();
+        match self.cut_buffer().get() {
             (content, ClipboardMode::Normal) => {
                 self.line_buffer.move_right();
+                let start = self.line_buffer.insertion_point();
                 self.line_buffer.insert_str(&content);
+                self.last_paste = Some(start..self.line_buffer.insertion_point());
             }
             (mut content, ClipboardMode::Lines) => {
                 // TODO: Simplify that?
@@ -306,6 +412,18 @@ impl Editor {
         }
     }
 
+    fn yank_pop(&mut self, last_paste: Option<Range<usize>>) {
+        if let Some(paste_range) = last_paste {
+            if let Some(older_cut) = self.kill_ring.rotate() {
+                let older_cut = older_cut.to_string();
+                let paste_end = paste_range.start + older_cut.len();
+                self.line_buffer.replace(paste_range.clone(), &older_cut);
+                self.line_buffer.set_insertion_point(paste_end);
+                self.last_paste = Some(paste_range.start..paste_end);
+            }
+        }
+    }
+
     fn move_right_until_char(&mut self, c: char, before_char: bool, current_line: bool) {
         if before_char {
             self.line_buffer.move_right_before(c, current_line);
@@ -327,11 +445,12 @@ impl Editor {
             // Saving the section of the string that will be deleted to be
             // stored into the buffer
             let extra = if before_char { 0 } else { c.len_utf8() };
-            let cut_slice =
-                &self.line_buffer.get_buffer()[self.line_buffer.insertion_point()..index + extra];
+            let cut_slice = self.line_buffer.get_buffer()
+                [self.line_buffer.insertion_point()..index + extra]
+                .to_string();
 
             if !cut_slice.is_empty() {
-                self.cut_buffer.set(cut_slice, ClipboardMode::Normal);
+                self.kill(&cut_slice, ClipboardMode::Normal, false);
 
                 if before_char {
                     self.line_buffer.delete_right_before_char(c, current_line);
@@ -347,11 +466,12 @@ impl Editor {
             // Saving the section of the string that will be deleted to be
             // stored into the buffer
             let extra = if before_char { c.len_utf8() } else { 0 };
-            let cut_slice =
-                &self.line_buffer.get_buffer()[index + extra..self.line_buffer.insertion_point()];
+            let cut_slice = self.line_buffer.get_buffer()
+                [index + extra..self.line_buffer.insertion_point()]
+                .to_string();
 
             if !cut_slice.is_empty() {
-                self.cut_buffer.set(cut_slice, ClipboardMode::Normal);
+                self.kill(&cut_slice, ClipboardMode::Normal, true);
 
                 if before_char {
                     self.line_buffer.delete_left_before_char(c, current_line);
@@ -371,6 +491,24 @@ impl Editor {
     }
 }
 
+/// Commands that store the removed text in the cut buffer
+fn is_kill(command: &EditCommand) -> bool {
+    matches!(
+        command,
+        EditCommand::CutCurrentLine
+            | EditCommand::CutFromStart
+            | EditCommand::CutFromLineStart
+            | EditCommand::CutToEnd
+            | EditCommand::CutToLineEnd
+            | EditCommand::CutWordLeft
+            | EditCommand::CutWordRight
+            | EditCommand::CutRightUntil(_)
+            | EditCommand::CutRightBefore(_)
+            | EditCommand::CutLeftUntil(_)
+            | EditCommand::CutLeftBefore(_)
+    )
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -408,4 +546,136 @@ mod test {
         editor.run_edit_command(&EditCommand::Redo);
         assert_eq!(editor.get_buffer(), "This is a test");
     }
+
+    #[test]
+    fn test_yank_pop_cycles_through_kill_ring() {
+        let mut editor = editor_with("alpha beta gamma");
+        editor.set_kill_ring_size(3);
+        editor.run_edit_command(&EditCommand::CutWordLeft);
+        editor.run_edit_command(&EditCommand::Backspace);
+        editor.run_edit_command(&EditCommand::CutWordLeft);
+        assert_eq!(editor.get_buffer(), "alpha ");
+
+        editor.run_edit_command(&EditCommand::PasteCutBufferBefore);
+        assert_eq!(editor.get_buffer(), "alpha beta");
+        editor.run_edit_command(&EditCommand::YankPop);
+        assert_eq!(editor.get_buffer(), "alpha gamma");
+        assert_eq!(editor.insertion_point(), 11);
+        editor.run_edit_command(&EditCommand::YankPop);
+        assert_eq!(editor.get_buffer(), "alpha beta");
+    }
+
+    #[test]
+    fn test_new_paste_restarts_yank_pop_at_latest_cut() {
+        let mut editor = editor_with("alpha beta gamma");
+        editor.set_kill_ring_size(3);
+        editor.run_edit_command(&EditCommand::CutWordLeft);
+        editor.run_edit_command(&EditCommand::Backspace);
+        editor.run_edit_command(&EditCommand::CutWordLeft);
+
+        editor.run_edit_command(&EditCommand::PasteCutBufferBefore);
+        editor.run_edit_command(&EditCommand::YankPop);
+        assert_eq!(editor.get_buffer(), "alpha gamma");
+
+        editor.run_edit_command(&EditCommand::InsertChar(' '));
+        editor.run_edit_command(&EditCommand::PasteCutBufferBefore);
+        assert_eq!(editor.get_buffer(), "alpha gamma beta");
+        editor.run_edit_command(&EditCommand::YankPop);
+        assert_eq!(editor.get_buffer(), "alpha gamma gamma");
+    }
+
+    #[test]
+    fn test_yank_pop_requires_previous_paste() {
+        let mut editor = editor_with("alpha beta");
+        editor.set_kill_ring_size(3);
+        editor.run_edit_command(&EditCommand::CutWordLeft);
+        editor.run_edit_command(&EditCommand::YankPop);
+        assert_eq!(editor.get_buffer(), "alpha ");
+    }
+
+    #[test]
+    fn test_consecutive_kills_share_one_entry() {
+        let mut editor = editor_with("alpha beta gamma");
+        editor.set_kill_ring_size(3);
+        editor.run_edit_command(&EditCommand::CutWordLeft);
+        editor.run_edit_command(&EditCommand::CutWordLeft);
+        assert_eq!(editor.get_buffer(), "alpha ");
+
+        editor.run_edit_command(&EditCommand::PasteCutBufferBefore);
+        assert_eq!(editor.get_buffer(), "alpha beta gamma");
+    }
+
+    #[test]
+    fn test_single_entry_kill_ring_keeps_last_cut() {
+        let mut editor = editor_with("alpha beta gamma");
+        editor.run_edit_command(&EditCommand::CutWordLeft);
+        editor.run_edit_command(&EditCommand::CutWordLeft);
+        editor.run_edit_command(&EditCommand::PasteCutBufferBefore);
+        assert_eq!(editor.get_buffer(), "alpha beta ");
+    }
+
+    #[test]
+    fn test_undo_keeps_word_boundary() {
+        let mut editor = editor_with("foo/bar");
+        editor.set_word_boundary(WordBoundary::Whitespace);
+        editor.run_edit_command(&EditCommand::Undo);
+        editor.run_edit_command(&EditCommand::InsertString("foo/bar".to_string()));
+        editor.run_edit_command(&EditCommand::CutWordLeft);
+        assert_eq!(editor.get_buffer(), "");
+    }
+
+    #[test]
+    fn test_undo_removes_typed_word_at_once() {
+        let mut editor = editor_with("");
+        for cmd in str_to_edit_commands("hello") {
+            editor.run_edit_command(&cmd);
+        }
+        editor.run_edit_command(&EditCommand::Undo);
+        assert_eq!(editor.get_buffer(), "");
+    }
+
+    #[test]
+    fn test_paste_is_its_own_undo_step() {
+        let mut editor = editor_with("");
+        for cmd in str_to_edit_commands("foo") {
+            editor.run_edit_command(&cmd);
+        }
+        editor.run_edit_command(&EditCommand::CutWordLeft);
+        for cmd in str_to_edit_commands("bar ") {
+            editor.run_edit_command(&cmd);
+        }
+        editor.run_edit_command(&EditCommand::PasteCutBufferBefore);
+        editor.run_edit_command(&EditCommand::InsertChar('x'));
+        assert_eq!(editor.get_buffer(), "bar foox");
+
+        editor.run_edit_command(&EditCommand::Undo);
+        assert_eq!(editor.get_buffer(), "bar foo");
+        editor.run_edit_command(&EditCommand::Undo);
+        assert_eq!(editor.get_buffer(), "bar ");
+        editor.run_edit_command(&EditCommand::Undo);
+        assert_eq!(editor.get_buffer(), "");
+        editor.run_edit_command(&EditCommand::Undo);
+        assert_eq!(editor.get_buffer(), "foo");
+    }
+
+    #[test]
+    fn test_peek_undo_shows_previous_state() {
+        let mut editor = editor_with("");
+        assert_eq!(editor.undo_stack_len(), 1);
+        assert!(editor.peek_undo().is_none());
+
+        for cmd in str_to_edit_commands("hello world") {
+            editor.run_edit_command(&cmd);
+        }
+        editor.run_edit_command(&EditCommand::MoveToStart);
+        assert_eq!(editor.undo_stack_len(), 4);
+
+        let previous = editor.peek_undo().unwrap();
+        assert_eq!(previous.get_buffer(), "hello world");
+        assert_eq!(previous.insertion_point(), 11);
+
+        editor.run_edit_command(&EditCommand::Undo);
+        assert_eq!(editor.insertion_point(), 11);
+        assert_eq!(editor.undo_stack_len(), 4);
+    }
 }
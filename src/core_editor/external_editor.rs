@@ -0,0 +1,192 @@
+use crate::utils::utf8_decoder::read_to_string_lossy;
+use std::{
+    env, fs,
+    io::{self, ErrorKind, Write},
+    path::PathBuf,
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+const TEMP_FILE_ATTEMPTS: usize = 100;
+
+/// Edits the buffer in an external program (bash `C-x C-e`)
+///
+/// The editor command is read from the first set environment variable,
+/// by default `$VISUAL` and then `$EDITOR`. The command may contain arguments,
+/// e.g. `code --wait`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalEditor {
+    env_vars: Vec<String>,
+}
+
+impl Default for ExternalEditor {
+    fn default() -> Self {
+        Self {
+            env_vars: vec!["VISUAL".to_string(), "EDITOR".to_string()],
+        }
+    }
+}
+
+impl ExternalEditor {
+    /// Builder that replaces the environment variables checked for the editor command
+    #[must_use]
+    pub fn with_env_vars(mut self, env_vars: Vec<String>) -> Self {
+        self.env_vars = env_vars;
+        self
+    }
+
+    /// Editor command from the first non empty environment variable
+    pub fn command(&self) -> Option<String> {
+        self.env_vars
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|command| !command.trim().is_empty())
+    }
+
+    /// Opens `content` in the editor and waits until it exits
+    ///
+    /// Returns `Ok(None)` if the edited file is empty, which is treated as a
+    /// cancelled edit. A single trailing newline added by the editor is removed.
+    pub fn edit(&self, content: &str) -> io::Result<Option<String>> {
+        let command = self
+            .command()
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no editor configured"))?;
+        let mut args = command.split_whitespace();
+        let program = args.next().unwrap_or_default();
+
+        let (path, mut file) = create_temp_file()?;
+        let written = file.write_all(content.as_bytes());
+        drop(file);
+        if let Err(err) = written {
+            let _ignore = fs::remove_file(&path);
+            return Err(err);
+        }
+
+        let edited = Command::new(program)
+            .args(args)
+            .arg(&path)
+            .status()
+            .and_then(|status| {
+                if status.success() {
+                    // The editor may save the file in another encoding
+                    fs::File::open(&path).and_then(read_to_string_lossy)
+                } else {
+                    Err(io::Error::other(format!("editor exited with {}", status)))
+                }
+            });
+        let _ignore = fs::remove_file(&path);
+
+        let edited = edited?;
+        let edited = edited
+            .strip_suffix("\r\n")
+            .or_else(|| edited.strip_suffix('\n'))
+            .unwrap_or(&edited);
+
+        if edited.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(edited.to_string()))
+        }
+    }
+}
+
+/// Creates a new file in the temp directory that only the user can read
+///
+/// An existing file or symlink with the same name is never opened, as the temp
+/// directory may be shared with other users. The next name is tried instead.
+fn create_temp_file() -> io::Result<(PathBuf, fs::File)> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    for _ in 0..TEMP_FILE_ATTEMPTS {
+        let count = TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = env::temp_dir().join(format!("reedline-{}-{}.txt", std::process::id(), count));
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Err(io::Error::new(
+        ErrorKind::AlreadyExists,
+        "no unused name for the temporary file",
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::tempdir;
+
+    // Every test uses its own variable as the tests run in parallel
+    fn editor_with_script(var: &str, script: &str) -> (ExternalEditor, tempfile::TempDir) {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("editor.sh");
+        fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        env::set_var(var, &path);
+
+        (
+            ExternalEditor::default().with_env_vars(vec![var.to_string()]),
+            dir,
+        )
+    }
+
+    #[test]
+    fn edited_content_is_returned() {
+        let (editor, _dir) =
+            editor_with_script("REEDLINE_TEST_EDITOR_APPEND", r#"echo " | wc -l" >> "$1""#);
+
+        assert_eq!(editor.edit("ls").unwrap(), Some("ls | wc -l".to_string()));
+    }
+
+    #[test]
+    fn invalid_utf8_is_replaced() {
+        let (editor, _dir) =
+            editor_with_script("REEDLINE_TEST_EDITOR_LATIN1", r#"printf 'caf\351' > "$1""#);
+
+        assert_eq!(editor.edit("ls").unwrap(), Some("caf\u{fffd}".to_string()));
+    }
+
+    #[test]
+    fn temp_file_is_only_readable_by_the_user() {
+        let (editor, _dir) = editor_with_script(
+            "REEDLINE_TEST_EDITOR_MODE",
+            r#"ls -l "$1" | cut -c1-10 > "$1""#,
+        );
+
+        assert_eq!(
+            editor.edit("secret").unwrap(),
+            Some("-rw-------".to_string())
+        );
+    }
+
+    #[test]
+    fn failing_editor_is_an_error() {
+        let (editor, _dir) = editor_with_script("REEDLINE_TEST_EDITOR_FAIL", "exit 1");
+
+        assert!(editor.edit("ls").is_err());
+    }
+
+    #[test]
+    fn emptied_file_cancels_edit() {
+        let (editor, _dir) = editor_with_script("REEDLINE_TEST_EDITOR_EMPTY", r#": > "$1""#);
+
+        assert_eq!(editor.edit("ls").unwrap(), None);
+    }
+
+    #[test]
+    fn missing_editor_is_an_error() {
+        let editor =
+            ExternalEditor::default().with_env_vars(vec!["REEDLINE_TEST_EDITOR_UNSET".to_string()]);
+
+        assert_eq!(editor.command(), None);
+        assert_eq!(editor.edit("ls").unwrap_err().kind(), ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::utils::text_manipulation;
+use std::collections::VecDeque;
+
+/// Emacs style ring of the most recent cuts
+///
+/// The newest entry is at the front. After a paste the ring can be rotated to
+/// replace the pasted text with older cuts (emacs `M-y`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KillRing {
+    entries: VecDeque<String>,
+    capacity: usize,
+    yank_index: usize,
+}
+
+impl Default for KillRing {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl KillRing {
+    /// Creates a kill ring holding up to `capacity` cuts (at least one)
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            yank_index: 0,
+        }
+    }
+
+    /// Maximum number of cuts kept in the ring
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Stores a new cut as the most recent entry, dropping the oldest one if the ring is full
+    pub fn push(&mut self, content: &str) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_back();
+        }
+        self.entries.push_front(content.to_string());
+        self.yank_index = 0;
+    }
+
+    /// Extends the most recent entry with a consecutive cut
+    ///
+    /// `prepend` is used for cuts to the left of the cursor so that the entry
+    /// keeps the order of the text in the buffer.
+    pub fn append(&mut self, content: &str, prepend: bool) {
+        match self.entries.front_mut() {
+            Some(latest) if prepend => latest.insert_str(0, content),
+            Some(latest) => latest.push_str(content),
+            None => self.entries.push_front(content.to_string()),
+        }
+        self.yank_index = 0;
+    }
+
+    /// The most recent entry
+    pub fn latest(&self) -> Option<&str> {
+        self.entries.front().map(String::as_str)
+    }
+
+    /// Starts a new paste at the most recent entry
+    pub fn reset_yank(&mut self) {
+        self.yank_index = 0;
+    }
+
+    /// Moves the yank position to the next older entry, wrapping around at the oldest one
+    pub fn rotate(&mut self) -> Option<&str> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        self.yank_index = (self.yank_index + 1) % self.entries.len();
+        self.entries.get(self.yank_index).map(String::as_str)
+    }
+
+    /// Overwrites all entries with zeros before removing them
+    pub fn zeroize(&mut self) {
+        self.entries.iter_mut().for_each(text_manipulation::zeroize);
+        self.entries.clear();
+        self.yank_index = 0;
+    }
+
+    /// Changes the capacity, dropping the oldest entries that no longer fit
+    pub fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.entries.truncate(self.capacity);
+        self.yank_index = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn push_drops_oldest_entry_when_full() {
+        let mut ring = KillRing::new(2);
+        ring.push("one");
+        ring.push("two");
+        ring.push("three");
+
+        assert_eq!(ring.latest(), Some("three"));
+        assert_eq!(ring.rotate(), Some("two"));
+        assert_eq!(ring.rotate(), Some("three"));
+    }
+
+    #[test]
+    fn append_extends_latest_entry() {
+        let mut ring = KillRing::new(3);
+        ring.push("two");
+        ring.append(" three", false);
+        ring.append("one ", true);
+
+        assert_eq!(ring.latest(), Some("one two three"));
+        assert_eq!(ring.rotate(), Some("one two three"));
+    }
+
+    #[test]
+    fn zero_capacity_keeps_one_entry() {
+        let mut ring = KillRing::new(0);
+        ring.push("one");
+        ring.push("two");
+
+        assert_eq!(ring.capacity(), 1);
+        assert_eq!(ring.latest(), Some("two"));
+    }
+}
@@ -1,4 +1,6 @@
 use {
+    super::WordBoundary,
+    crate::utils::text_manipulation,
     std::{convert::From, ops::Range},
     unicode_segmentation::UnicodeSegmentation,
 };
@@ -171,21 +173,20 @@ impl LineBuffer {
     }
 
     /// Cursor position *behind* the next word to the right
-    pub fn word_right_index(&self) -> usize {
-        self.lines[self.insertion_point..]
-            .split_word_bound_indices()
-            .find(|(_, word)| !is_word_boundary(word))
-            .map(|(i, word)| self.insertion_point + i + word.len())
+    pub fn word_right_index(&self, word_boundary: &WordBoundary) -> usize {
+        word_boundary
+            .words(&self.lines[self.insertion_point..])
+            .next()
+            .map(|word| self.insertion_point + word.end)
             .unwrap_or_else(|| self.lines.len())
     }
 
     /// Cursor position *in front of* the next word to the left
-    pub fn word_left_index(&self) -> usize {
-        self.lines[..self.insertion_point]
-            .split_word_bound_indices()
-            .filter(|(_, word)| !is_word_boundary(word))
+    pub fn word_left_index(&self, word_boundary: &WordBoundary) -> usize {
+        word_boundary
+            .words(&self.lines[..self.insertion_point])
             .last()
-            .map(|(i, _)| i)
+            .map(|word| word.start)
             .unwrap_or(0)
     }
 
@@ -200,13 +201,13 @@ impl LineBuffer {
     }
 
     /// Move cursor position *in front of* the next word to the left
-    pub fn move_word_left(&mut self) {
-        self.insertion_point = self.word_left_index();
+    pub fn move_word_left(&mut self, word_boundary: &WordBoundary) {
+        self.insertion_point = self.word_left_index(word_boundary);
     }
 
     /// Move cursor position *behind* the next word to the right
-    pub fn move_word_right(&mut self) {
-        self.insertion_point = self.word_right_index();
+    pub fn move_word_right(&mut self, word_boundary: &WordBoundary) {
+        self.insertion_point = self.word_right_index(word_boundary);
     }
 
     ///Insert a single character at the insertion point and move right
@@ -232,6 +233,12 @@ impl LineBuffer {
         self.insertion_point = 0;
     }
 
+    /// Overwrites the content with zeros before clearing it, e.g. after reading a password
+    pub(crate) fn zeroize(&mut self) {
+        text_manipulation::zeroize(&mut self.lines);
+        self.insertion_point = 0;
+    }
+
     /// Clear everything beginning at the cursor to the right/end.
     /// Keeps the cursor at the end.
     pub fn clear_to_end(&mut self) {
@@ -281,13 +288,12 @@ impl LineBuffer {
     }
 
     /// Gets the range of the word the current edit position is pointing to
-    pub fn current_word_range(&self) -> Range<usize> {
-        let right_index = self.word_right_index();
-        let left_index = self.lines[..right_index]
-            .split_word_bound_indices()
-            .filter(|(_, word)| !is_word_boundary(word))
+    pub fn current_word_range(&self, word_boundary: &WordBoundary) -> Range<usize> {
+        let right_index = self.word_right_index(word_boundary);
+        let left_index = word_boundary
+            .words(&self.lines[..right_index])
             .last()
-            .map(|(i, _)| i)
+            .map(|word| word.start)
             .unwrap_or(0);
 
         left_index..right_index
@@ -310,19 +316,19 @@ impl LineBuffer {
     }
 
     /// Uppercases the current word
-    pub fn uppercase_word(&mut self) {
-        let change_range = self.current_word_range();
+    pub fn uppercase_word(&mut self, word_boundary: &WordBoundary) {
+        let change_range = self.current_word_range(word_boundary);
         let uppercased = self.get_buffer()[change_range.clone()].to_uppercase();
         self.replace_range(change_range, &uppercased);
-        self.move_word_right();
+        self.move_word_right(word_boundary);
     }
 
     /// Lowercases the current word
-    pub fn lowercase_word(&mut self) {
-        let change_range = self.current_word_range();
+    pub fn lowercase_word(&mut self, word_boundary: &WordBoundary) {
+        let change_range = self.current_word_range(word_boundary);
         let uppercased = self.get_buffer()[change_range.clone()].to_lowercase();
         self.replace_range(change_range, &uppercased);
-        self.move_word_right();
+        self.move_word_right(word_boundary);
     }
 
     /// Counts the number of words in the buffer
@@ -333,10 +339,10 @@ impl LineBuffer {
     /// Capitalize the character at insertion point (or the first character
     /// following the whitespace at the insertion point) and move the insertion
     /// point right one grapheme.
-    pub fn capitalize_char(&mut self) {
+    pub fn capitalize_char(&mut self, word_boundary: &WordBoundary) {
         if self.on_whitespace() {
-            self.move_word_right();
-            self.move_word_left();
+            self.move_word_right(word_boundary);
+            self.move_word_left(word_boundary);
         }
         let insertion_offset = self.insertion_point();
         let right_index = self.grapheme_right_index();
@@ -369,26 +375,26 @@ impl LineBuffer {
     }
 
     /// Deletes one word to the left
-    pub fn delete_word_left(&mut self) {
-        let left_word_index = self.word_left_index();
+    pub fn delete_word_left(&mut self, word_boundary: &WordBoundary) {
+        let left_word_index = self.word_left_index(word_boundary);
         self.clear_range(left_word_index..self.insertion_point());
         self.insertion_point = left_word_index;
     }
 
     /// Deletes one word to the right
-    pub fn delete_word_right(&mut self) {
-        let right_word_index = self.word_right_index();
+    pub fn delete_word_right(&mut self, word_boundary: &WordBoundary) {
+        let right_word_index = self.word_right_index(word_boundary);
         self.clear_range(self.insertion_point()..right_word_index);
     }
 
     /// Swaps current word with word on right
-    pub fn swap_words(&mut self) {
-        let word_1_range = self.current_word_range();
-        self.move_word_right();
-        let word_2_range = self.current_word_range();
+    pub fn swap_words(&mut self, word_boundary: &WordBoundary) {
+        let word_1_range = self.current_word_range(word_boundary);
+        self.move_word_right(word_boundary);
+        let word_2_range = self.current_word_range(word_boundary);
 
         if word_1_range != word_2_range {
-            self.move_word_left();
+            self.move_word_left(word_boundary);
             let insertion_line = self.get_buffer();
             let word_1 = insertion_line[word_1_range.clone()].to_string();
             let word_2 = insertion_line[word_2_range.clone()].to_string();
@@ -576,11 +582,6 @@ impl LineBuffer {
     }
 }
 
-/// Match any sequence of characters that are considered a word boundary
-fn is_word_boundary(s: &str) -> bool {
-    !s.chars().any(char::is_alphanumeric)
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
@@ -688,7 +689,7 @@ mod test {
     #[test]
     fn delete_word_left_works() {
         let mut line_buffer = buffer_with("This is a test");
-        line_buffer.delete_word_left();
+        line_buffer.delete_word_left(&WordBoundary::default());
 
         let expected_line_buffer = buffer_with("This is a ");
 
@@ -696,11 +697,51 @@ mod test {
         line_buffer.assert_valid();
     }
 
+    #[rstest]
+    #[case(WordBoundary::Alphanumeric, 4)]
+    #[case(WordBoundary::Whitespace, 0)]
+    #[case(WordBoundary::Separators(vec!['/', '.']), 8)]
+    fn move_word_left_respects_word_boundary(
+        #[case] word_boundary: WordBoundary,
+        #[case] expected: usize,
+    ) {
+        let mut line_buffer = buffer_with("foo/bar.baz");
+        line_buffer.move_word_left(&word_boundary);
+
+        assert_eq!(line_buffer.insertion_point(), expected);
+        line_buffer.assert_valid();
+    }
+
+    #[rstest]
+    #[case(WordBoundary::Alphanumeric, 3)]
+    #[case(WordBoundary::Whitespace, 11)]
+    #[case(WordBoundary::Separators(vec!['/', '.']), 3)]
+    fn move_word_right_respects_word_boundary(
+        #[case] word_boundary: WordBoundary,
+        #[case] expected: usize,
+    ) {
+        let mut line_buffer = buffer_with("foo/bar.baz");
+        line_buffer.move_to_start();
+        line_buffer.move_word_right(&word_boundary);
+
+        assert_eq!(line_buffer.insertion_point(), expected);
+        line_buffer.assert_valid();
+    }
+
+    #[test]
+    fn delete_word_left_keeps_grapheme_clusters() {
+        let mut line_buffer = buffer_with("foo/bare\u{301}");
+        line_buffer.delete_word_left(&WordBoundary::Separators(vec!['/']));
+
+        assert_eq!(line_buffer.get_buffer(), "foo/");
+        line_buffer.assert_valid();
+    }
+
     #[test]
     fn delete_word_right_works() {
         let mut line_buffer = buffer_with("This is a test");
-        line_buffer.move_word_left();
-        line_buffer.delete_word_right();
+        line_buffer.move_word_left(&WordBoundary::default());
+        line_buffer.delete_word_right(&WordBoundary::default());
 
         let expected_line_buffer = buffer_with("This is a ");
 
@@ -731,7 +772,7 @@ mod test {
     ) {
         let mut line_buffer = buffer_with(input);
         line_buffer.set_insertion_point(in_location);
-        line_buffer.capitalize_char();
+        line_buffer.capitalize_char(&WordBoundary::default());
 
         let mut expected = buffer_with(output);
         expected.set_insertion_point(out_location);
@@ -754,7 +795,7 @@ mod test {
     ) {
         let mut line_buffer = buffer_with(input);
         line_buffer.set_insertion_point(in_location);
-        line_buffer.uppercase_word();
+        line_buffer.uppercase_word(&WordBoundary::default());
 
         let mut expected = buffer_with(output);
         expected.set_insertion_point(out_location);
@@ -777,7 +818,7 @@ mod test {
     ) {
         let mut line_buffer = buffer_with(input);
         line_buffer.set_insertion_point(in_location);
-        line_buffer.lowercase_word();
+        line_buffer.lowercase_word(&WordBoundary::default());
 
         let mut expected = buffer_with(output);
         expected.set_insertion_point(out_location);
@@ -822,7 +863,7 @@ mod test {
         let mut line_buffer = buffer_with(input);
         line_buffer.set_insertion_point(in_location);
 
-        line_buffer.swap_words();
+        line_buffer.swap_words(&WordBoundary::default());
 
         let mut expected = buffer_with(output);
         expected.set_insertion_point(out_location);
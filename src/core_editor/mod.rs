@@ -1,8 +1,13 @@
 mod clip_buffer;
 mod edit_stack;
 mod editor;
+mod external_editor;
+mod kill_ring;
 mod line_buffer;
+mod word_boundary;
 
 pub(crate) use clip_buffer::{get_default_clipboard, Clipboard, ClipboardMode};
 pub use editor::Editor;
+pub use external_editor::ExternalEditor;
 pub use line_buffer::LineBuffer;
+pub use word_boundary::WordBoundary;
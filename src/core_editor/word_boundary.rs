@@ -0,0 +1,74 @@
+use {std::ops::Range, unicode_segmentation::UnicodeSegmentation};
+
+/// Defines what is considered a word by the word-wise edit commands
+/// (e.g. [`crate::EditCommand::MoveWordLeft`] or [`crate::EditCommand::CutWordRight`])
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub enum WordBoundary {
+    /// Words as defined by the unicode word boundaries containing at least one
+    /// alphanumeric character (default)
+    #[default]
+    Alphanumeric,
+    /// Words are only separated by whitespace (like vi `W`)
+    Whitespace,
+    /// Words are separated by whitespace and the given characters
+    /// (e.g. `/` and `.` to move through paths)
+    Separators(Vec<char>),
+}
+
+impl WordBoundary {
+    /// Byte ranges of the words in `text`
+    pub(crate) fn words<'a>(
+        &'a self,
+        text: &'a str,
+    ) -> Box<dyn Iterator<Item = Range<usize>> + 'a> {
+        match self {
+            WordBoundary::Alphanumeric => Box::new(
+                text.split_word_bound_indices()
+                    .filter(|(_, word)| word.chars().any(char::is_alphanumeric))
+                    .map(|(i, word)| i..i + word.len()),
+            ),
+            WordBoundary::Whitespace | WordBoundary::Separators(_) => {
+                let mut graphemes = text.grapheme_indices(true).peekable();
+                Box::new(std::iter::from_fn(move || {
+                    let (start, first) = graphemes
+                        .by_ref()
+                        .find(|(_, grapheme)| !self.is_separator(grapheme))?;
+                    let mut end = start + first.len();
+                    while let Some((i, grapheme)) =
+                        graphemes.next_if(|(_, grapheme)| !self.is_separator(grapheme))
+                    {
+                        end = i + grapheme.len();
+                    }
+                    Some(start..end)
+                }))
+            }
+        }
+    }
+
+    fn is_separator(&self, grapheme: &str) -> bool {
+        grapheme.chars().all(|c| match self {
+            WordBoundary::Separators(separators) => c.is_whitespace() || separators.contains(&c),
+            _ => c.is_whitespace(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(WordBoundary::Alphanumeric, "foo  bar", vec![0..3, 5..8])]
+    #[case(WordBoundary::Whitespace, "foo/bar baz", vec![0..7, 8..11])]
+    #[case(WordBoundary::Separators(vec!['/', '.']), "foo/bar.baz", vec![0..3, 4..7, 8..11])]
+    #[case(WordBoundary::Separators(vec!['/']), "/e\u{301}/", vec![1..4])]
+    fn words_are_split_by_policy(
+        #[case] boundary: WordBoundary,
+        #[case] text: &str,
+        #[case] expected: Vec<Range<usize>>,
+    ) {
+        assert_eq!(boundary.words(text).collect::<Vec<_>>(), expected);
+    }
+}
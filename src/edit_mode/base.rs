@@ -1,5 +1,6 @@
 use crate::{enums::ReedlineEvent, PromptEditMode};
 use crossterm::event::Event;
+use std::time::Duration;
 
 /// Define the style of parsing for the edit events
 /// Available default options:
@@ -11,4 +12,14 @@ pub trait EditMode: Send {
 
     /// What to display in the prompt indicator
     fn edit_mode(&self) -> PromptEditMode;
+
+    /// Time to wait for the next key of a started key sequence, `None` if no sequence is pending
+    fn pending_sequence_timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Handles the keys of a pending key sequence once the next key didn't arrive in time
+    fn flush_pending_sequence(&mut self) -> ReedlineEvent {
+        ReedlineEvent::None
+    }
 }
@@ -0,0 +1,74 @@
+use crate::{PromptEditMode, PromptViMode};
+use crossterm::cursor::CursorShape;
+
+/// Maps the cursor shapes to the different edit modes (emacs, vi insert and vi normal)
+///
+/// If a shape is `None`, [`crate::Reedline`] leaves the cursor untouched in that mode.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CursorConfig {
+    /// Cursor shape while in vi insert mode
+    pub vi_insert: Option<CursorShape>,
+    /// Cursor shape while in vi normal mode
+    pub vi_normal: Option<CursorShape>,
+    /// Cursor shape while in emacs (or the default) mode
+    pub emacs: Option<CursorShape>,
+}
+
+impl CursorConfig {
+    /// Config with the common vi style: a beam in insert mode and a block in normal mode
+    pub fn vi_style() -> Self {
+        Self {
+            vi_insert: Some(CursorShape::Line),
+            vi_normal: Some(CursorShape::Block),
+            emacs: None,
+        }
+    }
+
+    /// Cursor shape that should be displayed for the given edit mode
+    pub fn shape_for(&self, edit_mode: &PromptEditMode) -> Option<CursorShape> {
+        match edit_mode {
+            PromptEditMode::Default | PromptEditMode::Emacs => self.emacs,
+            PromptEditMode::Vi(PromptViMode::Insert) => self.vi_insert,
+            PromptEditMode::Vi(PromptViMode::Normal) => self.vi_normal,
+            PromptEditMode::Custom(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crossterm::cursor::SetCursorShape;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn default_config_leaves_cursor_untouched() {
+        let config = CursorConfig::default();
+
+        assert_eq!(config.shape_for(&PromptEditMode::Emacs), None);
+        assert_eq!(
+            config.shape_for(&PromptEditMode::Vi(PromptViMode::Insert)),
+            None
+        );
+        assert_eq!(
+            config.shape_for(&PromptEditMode::Vi(PromptViMode::Normal)),
+            None
+        );
+    }
+
+    #[test]
+    fn vi_style_switches_between_beam_and_block() {
+        let config = CursorConfig::vi_style();
+
+        let insert = config
+            .shape_for(&PromptEditMode::Vi(PromptViMode::Insert))
+            .map(|shape| SetCursorShape(shape).to_string());
+        let normal = config
+            .shape_for(&PromptEditMode::Vi(PromptViMode::Normal))
+            .map(|shape| SetCursorShape(shape).to_string());
+
+        assert_eq!(insert, Some("\x1b[5 q".to_string()));
+        assert_eq!(normal, Some("\x1b[2 q".to_string()));
+        assert_eq!(config.shape_for(&PromptEditMode::Emacs), None);
+    }
+}
@@ -1,5 +1,8 @@
 use super::{
-    keybindings::{add_common_keybindings, edit_bind, Keybindings},
+    keybindings::{
+        add_common_keybindings, edit_bind, resolve_sequence_steps, KeyCombination, Keybindings,
+        PendingSequence, DEFAULT_SEQUENCE_TIMEOUT,
+    },
     EditMode,
 };
 use crate::{
@@ -7,6 +10,7 @@ use crate::{
     PromptEditMode,
 };
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use std::time::Duration;
 
 /// Returns the current default emacs keybindings
 pub fn default_emacs_keybindings() -> Keybindings {
@@ -62,9 +66,24 @@ pub fn default_emacs_keybindings() -> Keybindings {
         ]),
     );
     kb.add_binding(KM::ALT, KC::Char('d'), edit_bind(EC::CutWordRight));
+    kb.add_binding(KM::ALT, KC::Char('y'), edit_bind(EC::YankPop));
     kb.add_binding(KM::ALT, KC::Char('u'), edit_bind(EC::UppercaseWord));
     kb.add_binding(KM::ALT, KC::Char('l'), edit_bind(EC::LowercaseWord));
     kb.add_binding(KM::ALT, KC::Char('c'), edit_bind(EC::CapitalizeChar));
+    kb.add_binding(KM::ALT, KC::Char('e'), ReedlineEvent::OpenEditor);
+    kb.add_sequence_binding(
+        vec![
+            KeyCombination {
+                modifier: KM::CONTROL,
+                key_code: KC::Char('x'),
+            },
+            KeyCombination {
+                modifier: KM::CONTROL,
+                key_code: KC::Char('e'),
+            },
+        ],
+        ReedlineEvent::OpenEditor,
+    );
     kb.add_binding(
         KM::ALT,
         KC::Char('m'),
@@ -79,18 +98,56 @@ pub fn default_emacs_keybindings() -> Keybindings {
 /// This parses the incoming Events like a emacs style-editor
 pub struct Emacs {
     keybindings: Keybindings,
+    pending_sequence: PendingSequence,
+    sequence_timeout: Duration,
 }
 
 impl Default for Emacs {
     fn default() -> Self {
-        Emacs {
-            keybindings: default_emacs_keybindings(),
-        }
+        Emacs::new(default_emacs_keybindings())
     }
 }
 
 impl EditMode for Emacs {
     fn parse_event(&mut self, event: Event) -> ReedlineEvent {
+        let steps = self.pending_sequence.push(&self.keybindings, event);
+        resolve_sequence_steps(steps, |event| self.parse_key_event(event))
+    }
+
+    fn edit_mode(&self) -> PromptEditMode {
+        PromptEditMode::Emacs
+    }
+
+    fn pending_sequence_timeout(&self) -> Option<Duration> {
+        self.pending_sequence
+            .is_pending()
+            .then_some(self.sequence_timeout)
+    }
+
+    fn flush_pending_sequence(&mut self) -> ReedlineEvent {
+        let steps = self.pending_sequence.flush(&self.keybindings);
+        resolve_sequence_steps(steps, |event| self.parse_key_event(event))
+    }
+}
+
+impl Emacs {
+    /// Emacs style input parsing constructor if you want to use custom keybindings
+    pub fn new(keybindings: Keybindings) -> Self {
+        Emacs {
+            keybindings,
+            pending_sequence: PendingSequence::default(),
+            sequence_timeout: DEFAULT_SEQUENCE_TIMEOUT,
+        }
+    }
+
+    /// Builder to set how long to wait for the next key of a key sequence (default 1s)
+    #[must_use]
+    pub fn with_sequence_timeout(mut self, timeout: Duration) -> Self {
+        self.sequence_timeout = timeout;
+        self
+    }
+
+    fn parse_key_event(&self, event: Event) -> ReedlineEvent {
         match event {
             Event::Key(KeyEvent { code, modifiers }) => match (modifiers, code) {
                 (modifier, KeyCode::Char(c)) => {
@@ -107,7 +164,11 @@ impl EditMode for Emacs {
                         _ => c.to_ascii_lowercase(),
                     };
 
-                    if modifier == KeyModifiers::NONE
+                    // Bindings of typed characters are looked up first, e.g. a space
+                    // toggling the selection of a menu
+                    if let Some(event) = self.keybindings.find_binding(modifier, KeyCode::Char(c)) {
+                        event
+                    } else if modifier == KeyModifiers::NONE
                         || modifier == KeyModifiers::SHIFT
                         || modifier == KeyModifiers::CONTROL | KeyModifiers::ALT
                         || modifier
@@ -121,9 +182,7 @@ impl EditMode for Emacs {
                             },
                         )])
                     } else {
-                        self.keybindings
-                            .find_binding(modifier, KeyCode::Char(c))
-                            .unwrap_or(ReedlineEvent::None)
+                        ReedlineEvent::None
                     }
                 }
                 (KeyModifiers::NONE, KeyCode::Enter) => ReedlineEvent::Enter,
@@ -137,17 +196,6 @@ impl EditMode for Emacs {
             Event::Resize(width, height) => ReedlineEvent::Resize(width, height),
         }
     }
-
-    fn edit_mode(&self) -> PromptEditMode {
-        PromptEditMode::Emacs
-    }
-}
-
-impl Emacs {
-    /// Emacs style input parsing constructor if you want to use custom keybindings
-    pub fn new(keybindings: Keybindings) -> Self {
-        Emacs { keybindings }
-    }
 }
 
 #[cfg(test)]
@@ -167,6 +215,29 @@ mod test {
         assert_eq!(result, ReedlineEvent::ClearScreen);
     }
 
+    #[test]
+    fn ctrl_x_ctrl_e_opens_editor() {
+        let mut emacs = Emacs::default();
+        let ctrl = |c| {
+            Event::Key(KeyEvent {
+                modifiers: KeyModifiers::CONTROL,
+                code: KeyCode::Char(c),
+            })
+        };
+
+        assert_eq!(emacs.parse_event(ctrl('x')), ReedlineEvent::None);
+        assert!(emacs.pending_sequence_timeout().is_some());
+        assert_eq!(emacs.parse_event(ctrl('e')), ReedlineEvent::OpenEditor);
+        assert_eq!(emacs.pending_sequence_timeout(), None);
+
+        // Without the second key Ctrl-x is handled on its own once the timeout expires
+        emacs.parse_event(ctrl('x'));
+        assert_eq!(emacs.flush_pending_sequence(), ReedlineEvent::None);
+        // Another key ends the sequence and is handled normally
+        emacs.parse_event(ctrl('x'));
+        assert_eq!(emacs.parse_event(ctrl('l')), ReedlineEvent::ClearScreen);
+    }
+
     #[test]
     fn overriding_default_keybindings_works() {
         let mut keybindings = default_emacs_keybindings();
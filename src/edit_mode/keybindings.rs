@@ -1,21 +1,56 @@
 use {
     crate::{enums::ReedlineEvent, EditCommand},
-    crossterm::event::{KeyCode, KeyModifiers},
+    crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers},
     serde::{Deserialize, Serialize},
-    std::collections::HashMap,
+    std::{cmp::Ordering, collections::HashMap, mem, time::Duration},
 };
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
+/// How long the edit modes wait for the next key of a key sequence by default
+pub(crate) const DEFAULT_SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Key with its modifiers that a [`ReedlineEvent`] is bound to
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Hash, Debug)]
 pub struct KeyCombination {
+    /// Modifiers held down with the key
     pub modifier: KeyModifiers,
+    /// The pressed key
     pub key_code: KeyCode,
 }
 
+/// Problem with a keybinding found by [`Keybindings::find_conflicts`]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum KeybindingConflict {
+    /// The keys were bound to more than one event
+    Rebound {
+        /// The keys bound several times, more than one for a key sequence
+        keys: Vec<KeyCombination>,
+        /// Events in the order they were bound, only the last one is active
+        events: Vec<ReedlineEvent>,
+    },
+    /// The bound keys are also the start of longer key sequences
+    ///
+    /// Their event only runs once the sequence timeout expired or the next key
+    /// doesn't continue one of the sequences.
+    SequencePrefix {
+        /// The keys of the delayed binding
+        keys: Vec<KeyCombination>,
+        /// The longer sequences starting with the keys
+        sequences: Vec<Vec<KeyCombination>>,
+    },
+}
+
 /// Main definition of editor keybindings
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Keybindings {
     /// Defines a keybinding for a reedline event
     pub bindings: HashMap<KeyCombination, ReedlineEvent>,
+    /// Defines a reedline event for a sequence of keys (e.g. emacs `C-x C-e`)
+    #[serde(default)]
+    pub sequences: HashMap<Vec<KeyCombination>, ReedlineEvent>,
+    /// Bindings replaced by [`Keybindings::add_binding`] and
+    /// [`Keybindings::add_sequence_binding`], in the order they were replaced
+    #[serde(skip)]
+    replaced: Vec<(Vec<KeyCombination>, ReedlineEvent)>,
 }
 
 impl Default for Keybindings {
@@ -29,6 +64,8 @@ impl Keybindings {
     pub fn new() -> Self {
         Self {
             bindings: HashMap::new(),
+            sequences: HashMap::new(),
+            replaced: Vec::new(),
         }
     }
 
@@ -48,15 +85,55 @@ impl Keybindings {
         key_code: KeyCode,
         command: ReedlineEvent,
     ) {
-        if let ReedlineEvent::UntilFound(subcommands) = &command {
-            assert!(
-                !subcommands.is_empty(),
-                "UntilFound should contain a series of potential events to handle"
-            );
-        }
+        assert_valid_command(&command);
 
         let key_combo = KeyCombination { modifier, key_code };
-        self.bindings.insert(key_combo, command);
+        if let Some(replaced) = self.bindings.insert(key_combo.clone(), command.clone()) {
+            if replaced != command {
+                self.replaced.push((vec![key_combo], replaced));
+            }
+        }
+    }
+
+    /// Adds a keybinding for a sequence of keys
+    ///
+    /// After the first keys of a sequence the edit mode waits for the next key. If it
+    /// doesn't arrive in time or doesn't continue the sequence, the keys typed so far
+    /// are handled on their own. A sequence of a single key is added as a normal keybinding.
+    /// Like for [`Keybindings::add_binding`], characters typed with modifiers are
+    /// expected in lowercase.
+    ///
+    /// # Panics
+    ///
+    /// If `sequence` is empty or `command` is an empty [`ReedlineEvent::UntilFound`]
+    pub fn add_sequence_binding(&mut self, sequence: Vec<KeyCombination>, command: ReedlineEvent) {
+        assert!(
+            !sequence.is_empty(),
+            "A key sequence needs at least one key"
+        );
+
+        if let [key_combo] = sequence.as_slice() {
+            self.add_binding(key_combo.modifier, key_combo.key_code, command);
+        } else {
+            assert_valid_command(&command);
+            if let Some(replaced) = self.sequences.insert(sequence.clone(), command.clone()) {
+                if replaced != command {
+                    self.replaced.push((sequence, replaced));
+                }
+            }
+        }
+    }
+
+    /// Find the keybinding of a complete key sequence
+    pub fn find_sequence_binding(&self, sequence: &[KeyCombination]) -> Option<ReedlineEvent> {
+        self.sequences.get(sequence).cloned()
+    }
+
+    /// Checks if `keys` are the start of a longer bound key sequence
+    pub fn is_sequence_prefix(&self, keys: &[KeyCombination]) -> bool {
+        self.sequences
+            .keys()
+            .any(|sequence| sequence.len() > keys.len() && sequence.starts_with(keys))
     }
 
     /// Find a keybinding based on the modifier and keycode
@@ -65,12 +142,196 @@ impl Keybindings {
         self.bindings.get(&key_combo).cloned()
     }
 
+    /// Event bound to the modifier and keycode, e.g. to show why a key does nothing
+    pub fn describe(&self, modifier: KeyModifiers, key_code: KeyCode) -> Option<&ReedlineEvent> {
+        self.bindings.get(&KeyCombination { modifier, key_code })
+    }
+
+    /// Event bound to a key sequence, like [`Keybindings::describe`] for a single key
+    pub fn describe_sequence(&self, sequence: &[KeyCombination]) -> Option<&ReedlineEvent> {
+        match sequence {
+            [key_combo] => self.bindings.get(key_combo),
+            _ => self.sequences.get(sequence),
+        }
+    }
+
+    /// Keys that were bound to different events or that start a longer key sequence
+    ///
+    /// Keys rebound by [`Keybindings::add_binding`] or [`Keybindings::add_sequence_binding`]
+    /// come first, ordered by the first time a binding was replaced. Bindings inserted
+    /// directly into [`Keybindings::bindings`] or [`Keybindings::sequences`] are not
+    /// tracked as rebound. The bindings delayed by a longer sequence follow, sorted by
+    /// their keys.
+    pub fn find_conflicts(&self) -> Vec<KeybindingConflict> {
+        let mut rebound: Vec<(Vec<KeyCombination>, Vec<ReedlineEvent>)> = Vec::new();
+        for (keys, event) in &self.replaced {
+            match rebound
+                .iter_mut()
+                .find(|(rebound_keys, _)| rebound_keys == keys)
+            {
+                Some((_, events)) => events.push(event.clone()),
+                None => rebound.push((keys.clone(), vec![event.clone()])),
+            }
+        }
+
+        let mut conflicts: Vec<KeybindingConflict> = rebound
+            .into_iter()
+            .map(|(keys, mut events)| {
+                if let Some(event) = self.describe_sequence(&keys) {
+                    events.push(event.clone());
+                }
+                KeybindingConflict::Rebound { keys, events }
+            })
+            .collect();
+
+        let mut prefixes: Vec<Vec<KeyCombination>> = self
+            .bindings
+            .keys()
+            .map(|key_combo| vec![key_combo.clone()])
+            .chain(self.sequences.keys().cloned())
+            .filter(|keys| self.is_sequence_prefix(keys))
+            .collect();
+        prefixes.sort_by(|a, b| compare_keys(a, b));
+
+        conflicts.extend(prefixes.into_iter().map(|keys| {
+            let mut sequences: Vec<Vec<KeyCombination>> = self
+                .sequences
+                .keys()
+                .filter(|sequence| sequence.len() > keys.len() && sequence.starts_with(&keys))
+                .cloned()
+                .collect();
+            sequences.sort_by(|a, b| compare_keys(a, b));
+            KeybindingConflict::SequencePrefix { keys, sequences }
+        }));
+
+        conflicts
+    }
+
     /// Get assigned keybindings
     pub fn get_keybindings(&self) -> &HashMap<KeyCombination, ReedlineEvent> {
         &self.bindings
     }
 }
 
+fn compare_keys(a: &[KeyCombination], b: &[KeyCombination]) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+fn assert_valid_command(command: &ReedlineEvent) {
+    if let ReedlineEvent::UntilFound(subcommands) = command {
+        assert!(
+            !subcommands.is_empty(),
+            "UntilFound should contain a series of potential events to handle"
+        );
+    }
+}
+
+/// Outcome of feeding an event to a [`PendingSequence`]
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum SequenceStep {
+    /// A complete key sequence was typed
+    Bound(ReedlineEvent),
+    /// The event isn't part of a sequence and is parsed by the edit mode
+    Unbound(Event),
+}
+
+/// Keys typed so far of a started key sequence
+#[derive(Debug, Default)]
+pub(crate) struct PendingSequence {
+    keys: Vec<KeyCombination>,
+    events: Vec<Event>,
+}
+
+impl PendingSequence {
+    pub fn is_pending(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// Feeds the next event, returning nothing while a sequence is in progress
+    pub fn push(&mut self, keybindings: &Keybindings, event: Event) -> Vec<SequenceStep> {
+        let key_combo = match event {
+            Event::Key(KeyEvent { code, modifiers }) => KeyCombination {
+                modifier: modifiers,
+                key_code: match code {
+                    KeyCode::Char(c) if modifiers != KeyModifiers::NONE => {
+                        KeyCode::Char(c.to_ascii_lowercase())
+                    }
+                    code => code,
+                },
+            },
+            // Resizes or mouse events don't interrupt a sequence
+            _ => return vec![SequenceStep::Unbound(event)],
+        };
+
+        self.keys.push(key_combo);
+        self.events.push(event);
+
+        if keybindings.is_sequence_prefix(&self.keys) {
+            return vec![];
+        }
+        if let Some(command) = keybindings.find_sequence_binding(&self.keys) {
+            self.keys.clear();
+            self.events.clear();
+            return vec![SequenceStep::Bound(command)];
+        }
+
+        // The key doesn't continue the sequence: resolve the keys before it and start over
+        self.keys.pop();
+        let event = self.events.pop().expect("event was pushed with its key");
+        if self.keys.is_empty() {
+            return vec![SequenceStep::Unbound(event)];
+        }
+
+        let mut steps = self.flush(keybindings);
+        steps.extend(self.push(keybindings, event));
+        steps
+    }
+
+    /// Resolves the pending keys, e.g. once no further key arrived in time
+    ///
+    /// The longest bound sequence at the start is used, the other keys are fed again.
+    pub fn flush(&mut self, keybindings: &Keybindings) -> Vec<SequenceStep> {
+        let keys = mem::take(&mut self.keys);
+        let mut events = mem::take(&mut self.events);
+
+        let bound = (2..=keys.len())
+            .rev()
+            .find_map(|len| Some((len, keybindings.find_sequence_binding(&keys[..len])?)));
+
+        match bound {
+            Some((len, command)) => {
+                let mut steps = vec![SequenceStep::Bound(command)];
+                for event in events.drain(len..) {
+                    steps.extend(self.push(keybindings, event));
+                }
+                steps
+            }
+            None => events.into_iter().map(SequenceStep::Unbound).collect(),
+        }
+    }
+}
+
+/// Combines the outcome of a [`PendingSequence`] into a single event
+pub(crate) fn resolve_sequence_steps(
+    steps: Vec<SequenceStep>,
+    mut parse_event: impl FnMut(Event) -> ReedlineEvent,
+) -> ReedlineEvent {
+    let mut events: Vec<ReedlineEvent> = steps
+        .into_iter()
+        .map(|step| match step {
+            SequenceStep::Bound(command) => command,
+            SequenceStep::Unbound(event) => parse_event(event),
+        })
+        .filter(|event| *event != ReedlineEvent::None)
+        .collect();
+
+    match events.len() {
+        0 => ReedlineEvent::None,
+        1 => events.remove(0),
+        _ => ReedlineEvent::Multiple(events),
+    }
+}
+
 pub fn edit_bind(command: EditCommand) -> ReedlineEvent {
     ReedlineEvent::Edit(vec![command])
 }
@@ -92,6 +353,15 @@ pub fn add_common_keybindings(kb: &mut Keybindings) {
         ]),
     );
     kb.add_binding(KM::NONE, KC::Home, edit_bind(EC::MoveToLineStart));
+    // Typed as usual unless a menu in multi select mode is open
+    kb.add_binding(
+        KM::NONE,
+        KC::Char(' '),
+        ReedlineEvent::UntilFound(vec![
+            ReedlineEvent::MenuToggleSelection,
+            edit_bind(EC::InsertChar(' ')),
+        ]),
+    );
 
     kb.add_binding(KM::CONTROL, KC::Char('c'), ReedlineEvent::CtrlC);
     kb.add_binding(KM::CONTROL, KC::Char('l'), ReedlineEvent::ClearScreen);
@@ -157,3 +427,187 @@ pub fn add_common_keybindings(kb: &mut Keybindings) {
         ReedlineEvent::UntilFound(vec![ReedlineEvent::MenuDown, ReedlineEvent::Down]),
     );
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{default_emacs_keybindings, default_vi_normal_keybindings};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn rebound_key_is_reported_as_conflict() {
+        let mut keybindings = Keybindings::new();
+        keybindings.add_binding(
+            KeyModifiers::CONTROL,
+            KeyCode::Char('l'),
+            ReedlineEvent::ClearScreen,
+        );
+        keybindings.add_binding(
+            KeyModifiers::CONTROL,
+            KeyCode::Char('d'),
+            ReedlineEvent::CtrlD,
+        );
+        keybindings.add_binding(
+            KeyModifiers::CONTROL,
+            KeyCode::Char('l'),
+            ReedlineEvent::CtrlC,
+        );
+
+        assert_eq!(
+            keybindings.find_conflicts(),
+            vec![KeybindingConflict::Rebound {
+                keys: vec![KeyCombination {
+                    modifier: KeyModifiers::CONTROL,
+                    key_code: KeyCode::Char('l'),
+                }],
+                events: vec![ReedlineEvent::ClearScreen, ReedlineEvent::CtrlC],
+            }]
+        );
+        assert_eq!(
+            keybindings.describe(KeyModifiers::CONTROL, KeyCode::Char('l')),
+            Some(&ReedlineEvent::CtrlC)
+        );
+        assert_eq!(
+            keybindings.describe(KeyModifiers::ALT, KeyCode::Char('l')),
+            None
+        );
+    }
+
+    #[test]
+    fn binding_the_same_event_again_is_no_conflict() {
+        let mut keybindings = Keybindings::new();
+        keybindings.add_binding(KeyModifiers::NONE, KeyCode::Esc, ReedlineEvent::Esc);
+        keybindings.add_binding(KeyModifiers::NONE, KeyCode::Esc, ReedlineEvent::Esc);
+
+        assert_eq!(keybindings.find_conflicts(), vec![]);
+    }
+
+    fn key(c: char) -> KeyCombination {
+        KeyCombination {
+            modifier: KeyModifiers::NONE,
+            key_code: KeyCode::Char(c),
+        }
+    }
+
+    fn key_event(c: char) -> Event {
+        Event::Key(KeyEvent {
+            modifiers: KeyModifiers::NONE,
+            code: KeyCode::Char(c),
+        })
+    }
+
+    fn sequence_keybindings() -> Keybindings {
+        let mut keybindings = Keybindings::new();
+        keybindings.add_sequence_binding(vec![key('g'), key('g')], ReedlineEvent::Up);
+        keybindings.add_sequence_binding(vec![key('g'), key('g'), key('x')], ReedlineEvent::Down);
+        keybindings
+    }
+
+    #[test]
+    fn completed_sequence_is_bound() {
+        let keybindings = sequence_keybindings();
+        let mut pending = PendingSequence::default();
+
+        assert_eq!(pending.push(&keybindings, key_event('g')), vec![]);
+        assert_eq!(pending.push(&keybindings, key_event('g')), vec![]);
+        assert_eq!(
+            pending.push(&keybindings, key_event('x')),
+            vec![SequenceStep::Bound(ReedlineEvent::Down)]
+        );
+        assert!(!pending.is_pending());
+    }
+
+    #[test]
+    fn timed_out_prefix_falls_back_to_its_own_keys() {
+        let keybindings = sequence_keybindings();
+        let mut pending = PendingSequence::default();
+
+        pending.push(&keybindings, key_event('g'));
+        assert_eq!(
+            pending.flush(&keybindings),
+            vec![SequenceStep::Unbound(key_event('g'))]
+        );
+
+        // The longest bound sequence wins once the timeout expires
+        pending.push(&keybindings, key_event('g'));
+        pending.push(&keybindings, key_event('g'));
+        assert_eq!(
+            pending.flush(&keybindings),
+            vec![SequenceStep::Bound(ReedlineEvent::Up)]
+        );
+        assert!(!pending.is_pending());
+    }
+
+    #[test]
+    fn ambiguous_partial_is_resolved_by_next_key() {
+        let keybindings = sequence_keybindings();
+        let mut pending = PendingSequence::default();
+
+        pending.push(&keybindings, key_event('g'));
+        assert_eq!(
+            pending.push(&keybindings, key_event('a')),
+            vec![
+                SequenceStep::Unbound(key_event('g')),
+                SequenceStep::Unbound(key_event('a')),
+            ]
+        );
+
+        pending.push(&keybindings, key_event('g'));
+        pending.push(&keybindings, key_event('g'));
+        // `gg` is complete but `ggx` was still possible, the new key starts over
+        assert_eq!(
+            pending.push(&keybindings, key_event('g')),
+            vec![SequenceStep::Bound(ReedlineEvent::Up)]
+        );
+        assert!(pending.is_pending());
+    }
+
+    #[test]
+    fn rebound_sequence_is_reported_as_conflict() {
+        let mut keybindings = sequence_keybindings();
+        keybindings.add_sequence_binding(vec![key('g'), key('g')], ReedlineEvent::Esc);
+
+        assert_eq!(
+            keybindings.find_conflicts()[0],
+            KeybindingConflict::Rebound {
+                keys: vec![key('g'), key('g')],
+                events: vec![ReedlineEvent::Up, ReedlineEvent::Esc],
+            }
+        );
+        assert_eq!(
+            keybindings.describe_sequence(&[key('g'), key('g')]),
+            Some(&ReedlineEvent::Esc)
+        );
+        assert_eq!(keybindings.describe_sequence(&[key('g'), key('x')]), None);
+    }
+
+    #[test]
+    fn bindings_delayed_by_a_sequence_are_reported() {
+        let mut keybindings = sequence_keybindings();
+        keybindings.add_binding(KeyModifiers::NONE, KeyCode::Char('g'), ReedlineEvent::Enter);
+
+        assert_eq!(
+            keybindings.find_conflicts(),
+            vec![
+                KeybindingConflict::SequencePrefix {
+                    keys: vec![key('g')],
+                    sequences: vec![vec![key('g'), key('g')], vec![key('g'), key('g'), key('x')]],
+                },
+                KeybindingConflict::SequencePrefix {
+                    keys: vec![key('g'), key('g')],
+                    sequences: vec![vec![key('g'), key('g'), key('x')]],
+                },
+            ]
+        );
+        assert_eq!(
+            keybindings.describe_sequence(&[key('g')]),
+            Some(&ReedlineEvent::Enter)
+        );
+    }
+
+    #[test]
+    fn default_keybindings_have_no_conflicts() {
+        assert_eq!(default_emacs_keybindings().find_conflicts(), vec![]);
+        assert_eq!(default_vi_normal_keybindings().find_conflicts(), vec![]);
+    }
+}
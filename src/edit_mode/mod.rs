@@ -1,9 +1,11 @@
 mod base;
+mod cursors;
 mod emacs;
 mod keybindings;
 mod vi;
 
 pub use base::EditMode;
+pub use cursors::CursorConfig;
 pub use emacs::{default_emacs_keybindings, Emacs};
-pub use keybindings::Keybindings;
+pub use keybindings::{KeyCombination, KeybindingConflict, Keybindings};
 pub use vi::{default_vi_insert_keybindings, default_vi_normal_keybindings, Vi};
@@ -4,11 +4,17 @@ mod parser;
 mod vi_keybindings;
 
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use std::time::Duration;
 pub use vi_keybindings::{default_vi_insert_keybindings, default_vi_normal_keybindings};
 
 use super::EditMode;
 use crate::{
-    edit_mode::{keybindings::Keybindings, vi::parser::parse},
+    edit_mode::{
+        keybindings::{
+            resolve_sequence_steps, Keybindings, PendingSequence, DEFAULT_SEQUENCE_TIMEOUT,
+        },
+        vi::parser::parse,
+    },
     enums::{EditCommand, ReedlineEvent},
     PromptEditMode, PromptViMode,
 };
@@ -26,17 +32,16 @@ pub struct Vi {
     normal_keybindings: Keybindings,
     mode: ViMode,
     previous: Option<ReedlineEvent>,
+    pending_sequence: PendingSequence,
+    sequence_timeout: Duration,
 }
 
 impl Default for Vi {
     fn default() -> Self {
-        Vi {
-            insert_keybindings: default_vi_insert_keybindings(),
-            normal_keybindings: default_vi_normal_keybindings(),
-            cache: Vec::new(),
-            mode: ViMode::Insert,
-            previous: None,
-        }
+        Vi::new(
+            default_vi_insert_keybindings(),
+            default_vi_normal_keybindings(),
+        )
     }
 }
 
@@ -49,12 +54,19 @@ impl Vi {
             cache: Vec::new(),
             mode: ViMode::Insert,
             previous: None,
+            pending_sequence: PendingSequence::default(),
+            sequence_timeout: DEFAULT_SEQUENCE_TIMEOUT,
         }
     }
-}
 
-impl EditMode for Vi {
-    fn parse_event(&mut self, event: Event) -> ReedlineEvent {
+    /// Builder to set how long to wait for the next key of a key sequence (default 1s)
+    #[must_use]
+    pub fn with_sequence_timeout(mut self, timeout: Duration) -> Self {
+        self.sequence_timeout = timeout;
+        self
+    }
+
+    fn parse_key_event(&mut self, event: Event) -> ReedlineEvent {
         match event {
             Event::Key(KeyEvent { code, modifiers }) => match (self.mode, modifiers, code) {
                 (ViMode::Normal, modifier, KeyCode::Char(c)) => {
@@ -119,7 +131,14 @@ impl EditMode for Vi {
                         _ => c.to_ascii_lowercase(),
                     };
 
-                    if modifier == KeyModifiers::NONE
+                    // Bindings of typed characters are looked up first, e.g. a space
+                    // toggling the selection of a menu
+                    if let Some(event) = self
+                        .insert_keybindings
+                        .find_binding(modifier, KeyCode::Char(c))
+                    {
+                        event
+                    } else if modifier == KeyModifiers::NONE
                         || modifier == KeyModifiers::SHIFT
                         || modifier == KeyModifiers::CONTROL | KeyModifiers::ALT
                         || modifier
@@ -133,9 +152,7 @@ impl EditMode for Vi {
                             },
                         )])
                     } else {
-                        self.insert_keybindings
-                            .find_binding(modifier, KeyCode::Char(c))
-                            .unwrap_or(ReedlineEvent::None)
+                        ReedlineEvent::None
                     }
                 }
                 (_, KeyModifiers::NONE, KeyCode::Esc) => {
@@ -161,6 +178,23 @@ impl EditMode for Vi {
             Event::Resize(width, height) => ReedlineEvent::Resize(width, height),
         }
     }
+}
+
+impl EditMode for Vi {
+    fn parse_event(&mut self, event: Event) -> ReedlineEvent {
+        // The key completing a started vi command (e.g. the character after `f`)
+        // never starts a key sequence
+        if !self.cache.is_empty() {
+            return self.parse_key_event(event);
+        }
+
+        let keybindings = match self.mode {
+            ViMode::Normal => &self.normal_keybindings,
+            ViMode::Insert => &self.insert_keybindings,
+        };
+        let steps = self.pending_sequence.push(keybindings, event);
+        resolve_sequence_steps(steps, |event| self.parse_key_event(event))
+    }
 
     fn edit_mode(&self) -> PromptEditMode {
         match self.mode {
@@ -168,6 +202,21 @@ impl EditMode for Vi {
             ViMode::Insert => PromptEditMode::Vi(PromptViMode::Insert),
         }
     }
+
+    fn pending_sequence_timeout(&self) -> Option<Duration> {
+        self.pending_sequence
+            .is_pending()
+            .then_some(self.sequence_timeout)
+    }
+
+    fn flush_pending_sequence(&mut self) -> ReedlineEvent {
+        let keybindings = match self.mode {
+            ViMode::Normal => &self.normal_keybindings,
+            ViMode::Insert => &self.insert_keybindings,
+        };
+        let steps = self.pending_sequence.flush(keybindings);
+        resolve_sequence_steps(steps, |event| self.parse_key_event(event))
+    }
 }
 
 #[cfg(test)]
@@ -206,6 +255,8 @@ mod test {
             cache: Vec::new(),
             mode: ViMode::Normal,
             previous: None,
+            pending_sequence: PendingSequence::default(),
+            sequence_timeout: DEFAULT_SEQUENCE_TIMEOUT,
         };
 
         let esc = Event::Key(KeyEvent {
@@ -232,6 +283,8 @@ mod test {
             cache: Vec::new(),
             mode: ViMode::Normal,
             previous: None,
+            pending_sequence: PendingSequence::default(),
+            sequence_timeout: DEFAULT_SEQUENCE_TIMEOUT,
         };
 
         let esc = Event::Key(KeyEvent {
@@ -252,6 +305,8 @@ mod test {
             cache: Vec::new(),
             mode: ViMode::Normal,
             previous: None,
+            pending_sequence: PendingSequence::default(),
+            sequence_timeout: DEFAULT_SEQUENCE_TIMEOUT,
         };
 
         let esc = Event::Key(KeyEvent {
@@ -262,4 +317,53 @@ mod test {
 
         assert_eq!(result, ReedlineEvent::None);
     }
+
+    #[test]
+    fn gg_moves_to_start_in_normal_mode() {
+        let mut vi = Vi::default();
+        let key = |code| {
+            Event::Key(KeyEvent {
+                modifiers: KeyModifiers::NONE,
+                code,
+            })
+        };
+        vi.parse_event(key(KeyCode::Esc));
+
+        assert_eq!(vi.parse_event(key(KeyCode::Char('g'))), ReedlineEvent::None);
+        assert_eq!(
+            vi.parse_event(key(KeyCode::Char('g'))),
+            ReedlineEvent::Edit(vec![EditCommand::MoveToStart])
+        );
+    }
+
+    #[test]
+    fn argument_of_vi_command_is_not_part_of_a_sequence() {
+        let mut vi = Vi::default();
+        let key = |code| {
+            Event::Key(KeyEvent {
+                modifiers: KeyModifiers::NONE,
+                code,
+            })
+        };
+        vi.parse_event(key(KeyCode::Esc));
+
+        assert_eq!(vi.parse_event(key(KeyCode::Char('f'))), ReedlineEvent::None);
+        assert_eq!(
+            vi.parse_event(key(KeyCode::Char('g'))),
+            ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![
+                EditCommand::MoveRightUntil('g')
+            ])])
+        );
+        assert!(vi.pending_sequence_timeout().is_none());
+
+        // `fgg` moves to the `g` and then starts `gg`
+        vi.parse_event(key(KeyCode::Char('f')));
+        vi.parse_event(key(KeyCode::Char('g')));
+        assert_eq!(vi.parse_event(key(KeyCode::Char('g'))), ReedlineEvent::None);
+        assert!(vi.cache.is_empty());
+        assert_eq!(
+            vi.parse_event(key(KeyCode::Char('g'))),
+            ReedlineEvent::Edit(vec![EditCommand::MoveToStart])
+        );
+    }
 }
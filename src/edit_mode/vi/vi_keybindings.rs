@@ -1,6 +1,9 @@
 use crate::{
-    edit_mode::{keybindings::add_common_keybindings, Keybindings},
-    ReedlineEvent,
+    edit_mode::{
+        keybindings::{add_common_keybindings, edit_bind},
+        KeyCombination, Keybindings,
+    },
+    EditCommand, ReedlineEvent,
 };
 
 use crossterm::event::{KeyCode as KC, KeyModifiers as KM};
@@ -32,6 +35,12 @@ pub fn default_vi_normal_keybindings() -> Keybindings {
         ReedlineEvent::UntilFound(vec![ReedlineEvent::MenuRight, ReedlineEvent::Right]),
     );
 
+    let g = KeyCombination {
+        modifier: KM::NONE,
+        key_code: KC::Char('g'),
+    };
+    kb.add_sequence_binding(vec![g.clone(), g], edit_bind(EditCommand::MoveToStart));
+
     kb
 }
 
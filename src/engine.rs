@@ -1,14 +1,14 @@
 use {
     crate::{
         completion::{CircularCompletionHandler, Completer, DefaultCompleter},
-        core_editor::Editor,
-        edit_mode::{EditMode, Emacs},
+        core_editor::{Editor, ExternalEditor, LineBuffer, WordBoundary},
+        edit_mode::{CursorConfig, EditMode, Emacs},
         enums::{EventStatus, ReedlineEvent},
         highlighter::SimpleMatchHighlighter,
         hinter::{DefaultHinter, Hinter},
-        history::{FileBackedHistory, History, HistoryNavigationQuery},
-        menu::{Menu, MenuEvent, ReedlineMenu},
-        painting::{Painter, PromptLines},
+        history::{FileBackedHistory, History, HistoryError, HistoryNavigationQuery},
+        menu::{HistorySearchStyle, Menu, MenuEvent, ReedlineMenu},
+        painting::{Painter, PromptLines, RepaintThrottle},
         prompt::{PromptEditMode, PromptHistorySearchStatus},
         utils::text_manipulation,
         DefaultValidator, EditCommand, ExampleHighlighter, Highlighter, Prompt,
@@ -19,7 +19,12 @@ use {
         event::{Event, KeyCode, KeyEvent, KeyModifiers},
         terminal, Result,
     },
-    std::{borrow::Borrow, io, time::Duration},
+    std::{
+        borrow::Borrow,
+        io,
+        time::{Duration, Instant},
+    },
+    unicode_segmentation::UnicodeSegmentation,
 };
 
 #[cfg(feature = "bashisms")]
@@ -31,6 +36,10 @@ use crate::menu_functions::{parse_selection_char, ParseAction};
 // arrives. This doesn't allow for the possibility of more than 1 event
 // happening at the same time.
 const POLL_WAIT: u64 = 10;
+
+// Interval to check for the result of an asynchronous highlighter
+const HIGHLIGHT_POLL_WAIT: u64 = 50;
+
 // Since a paste event is multiple Event::Key events happening at the same time, we specify
 // how many events should be in the crossterm_events vector before it is considered
 // a paste. 10 events in 10 milliseconds is conservative enough (unlikely somebody
@@ -90,6 +99,9 @@ pub struct Reedline {
     // Edit Mode: Vi, Emacs
     edit_mode: Box<dyn EditMode>,
 
+    // Cursor shapes for the different edit modes
+    cursor_config: CursorConfig,
+
     // Provides the tab completions
     completer: Box<dyn Completer>,
     quick_completions: bool,
@@ -108,11 +120,24 @@ pub struct Reedline {
     // Is Some(n) read_line() should repaint prompt every `n` milliseconds
     animate: bool,
 
+    // Coalesces the repaints of rapid edits, e.g. for slow terminals
+    repaint_throttle: RepaintThrottle,
+
     // Use ansi coloring or not
     use_ansi_coloring: bool,
 
+    // Colors of the history reverse search
+    history_search_style: HistorySearchStyle,
+
     // Engine Menus
     menus: Vec<ReedlineMenu>,
+
+    // Editor used to edit the buffer with `ReedlineEvent::OpenEditor`
+    external_editor: ExternalEditor,
+
+    // Set while `read_secret()` is running, `secret_mask` is shown for every typed character
+    secret_input: bool,
+    secret_mask: Option<char>,
 }
 
 impl Drop for Reedline {
@@ -141,6 +166,7 @@ impl Reedline {
             input_mode: InputMode::Regular,
             painter,
             edit_mode,
+            cursor_config: CursorConfig::default(),
             completer,
             quick_completions: false,
             partial_completions: false,
@@ -150,8 +176,13 @@ impl Reedline {
             hide_hints: false,
             validator,
             animate: false,
+            repaint_throttle: RepaintThrottle::default(),
             use_ansi_coloring: true,
+            history_search_style: HistorySearchStyle::default(),
             menus: Vec::new(),
+            external_editor: ExternalEditor::default(),
+            secret_input: false,
+            secret_mask: None,
         }
     }
 
@@ -224,6 +255,25 @@ impl Reedline {
         self
     }
 
+    /// A builder that sets the colors of the entry found by the history reverse search
+    ///
+    /// Without ansi coloring the entry is shown unstyled.
+    /// # Example
+    /// ```rust,no_run
+    /// use nu_ansi_term::{Color, Style};
+    /// use reedline::{HistorySearchStyle, Reedline};
+    ///
+    /// let mut line_editor = Reedline::create().with_history_search_style(HistorySearchStyle {
+    ///     match_style: Color::Black.on(Color::Yellow),
+    ///     text_style: Style::new().italic(),
+    /// });
+    /// ```
+    #[must_use]
+    pub fn with_history_search_style(mut self, history_search_style: HistorySearchStyle) -> Self {
+        self.history_search_style = history_search_style;
+        self
+    }
+
     /// A builder which enables or disables animations/automatic repainting of prompt.
     /// If `repaint` is true, every second the prompt will be repainted and the clock updates
     #[must_use]
@@ -232,6 +282,24 @@ impl Reedline {
         self
     }
 
+    /// A builder that sets the minimum time between two repaints while typing (default none)
+    ///
+    /// Edits arriving faster are painted together, which reduces the output for slow
+    /// terminals, e.g. over SSH. The latest state is painted as soon as the input pauses.
+    #[must_use]
+    pub fn with_repaint_interval(mut self, interval: Duration) -> Self {
+        self.repaint_throttle = RepaintThrottle::new(interval);
+        self
+    }
+
+    /// A builder that sets the character shown for every typed character of
+    /// [`Reedline::read_secret()`] (default: nothing is shown)
+    #[must_use]
+    pub fn with_secret_mask(mut self, mask: Option<char>) -> Self {
+        self.secret_mask = mask;
+        self
+    }
+
     /// A builder that configures the highlighter for your instance of the Reedline engine
     /// # Example
     /// ```rust,no_run
@@ -297,6 +365,70 @@ impl Reedline {
         self
     }
 
+    /// A builder that configures the cursor shape for each edit mode
+    /// # Example
+    /// ```rust,no_run
+    /// // Show a beam in vi insert mode and a block in vi normal mode
+    ///
+    /// use reedline::{CursorConfig, Reedline, Vi};
+    ///
+    /// let mut line_editor = Reedline::create()
+    ///     .with_edit_mode(Box::new(Vi::default()))
+    ///     .with_cursor_config(CursorConfig::vi_style());
+    /// ```
+    #[must_use]
+    pub fn with_cursor_config(mut self, cursor_config: CursorConfig) -> Self {
+        self.cursor_config = cursor_config;
+        self
+    }
+
+    /// A builder that configures how the editor for [`ReedlineEvent::OpenEditor`] is found
+    /// # Example
+    /// ```rust
+    /// use reedline::{ExternalEditor, Reedline};
+    ///
+    /// let line_editor = Reedline::create()
+    ///     .with_external_editor(ExternalEditor::default().with_env_vars(vec!["MY_EDITOR".into()]));
+    /// ```
+    #[must_use]
+    pub fn with_external_editor(mut self, external_editor: ExternalEditor) -> Self {
+        self.external_editor = external_editor;
+        self
+    }
+
+    /// A builder that defines what is considered a word by the word-wise edit commands
+    /// # Example
+    /// ```rust,no_run
+    /// // Stop at path separators when moving by words
+    ///
+    /// use reedline::{Reedline, WordBoundary};
+    ///
+    /// let mut line_editor =
+    ///     Reedline::create().with_word_boundary(WordBoundary::Separators(vec!['/', '.']));
+    /// ```
+    #[must_use]
+    pub fn with_word_boundary(mut self, word_boundary: WordBoundary) -> Self {
+        self.editor.set_word_boundary(word_boundary);
+        self
+    }
+
+    /// A builder that sets the number of cuts kept in the kill ring
+    ///
+    /// With more than one entry, consecutive cuts are accumulated into one entry
+    /// and [`EditCommand::YankPop`] (emacs `M-y`) replaces the text just pasted
+    /// with older cuts.
+    /// # Example
+    /// ```rust,no_run
+    /// use reedline::Reedline;
+    ///
+    /// let mut line_editor = Reedline::create().with_kill_ring_size(10);
+    /// ```
+    #[must_use]
+    pub fn with_kill_ring_size(mut self, size: usize) -> Self {
+        self.editor.set_kill_ring_size(size);
+        self
+    }
+
     /// A builder that appends a menu to the engine
     #[must_use]
     pub fn with_menu(mut self, menu: Box<dyn Menu>, completer: Option<Box<dyn Completer>>) -> Self {
@@ -310,6 +442,16 @@ impl Reedline {
         self
     }
 
+    /// Number of states in the undo stack of the current line, including the ones that can be redone
+    pub fn undo_stack_len(&self) -> usize {
+        self.editor.undo_stack_len()
+    }
+
+    /// Line buffer (content and cursor position) that the next undo would restore
+    pub fn peek_undo(&self) -> Option<&LineBuffer> {
+        self.editor.peek_undo()
+    }
+
     /// Returns the corresponding expected prompt style for the given edit mode
     pub fn prompt_edit_mode(&self) -> PromptEditMode {
         self.edit_mode.edit_mode()
@@ -336,7 +478,7 @@ impl Reedline {
     }
 
     /// Update the underlying [`History`] to/from disk
-    pub fn sync_history(&mut self) -> std::io::Result<()> {
+    pub fn sync_history(&mut self) -> std::result::Result<(), HistoryError> {
         // TODO: check for interactions in the non-submitting events
         self.history.sync()
     }
@@ -350,12 +492,42 @@ impl Reedline {
         terminal::enable_raw_mode()?;
 
         let result = self.read_line_helper(prompt);
+        let result = self.finish_read_line(result);
 
         terminal::disable_raw_mode()?;
 
         result
     }
 
+    /// Cleans up the terminal once the read loop returned, also if it failed
+    fn finish_read_line(&mut self, result: Result<Signal>) -> Result<Signal> {
+        // The output following the prompt should not keep the cursor of the edit mode
+        let reset = self.painter.reset_cursor_shape();
+
+        result.and_then(|signal| reset.map(|_| signal))
+    }
+
+    /// Reads a secret like a password without showing or storing it
+    ///
+    /// The typed characters are not echoed, or only as the mask set with
+    /// [`Reedline::with_secret_mask()`]. Hints, completions, highlighting, history
+    /// navigation and the external editor are disabled, and the input is not added to
+    /// the history. Cuts go to a private clipboard instead of the one used by
+    /// [`Reedline::read_line()`]. Once the input is accepted or cancelled the edit
+    /// buffer, its undo states, the kill ring and the private clipboard are overwritten.
+    pub fn read_secret(&mut self, prompt: &dyn Prompt) -> Result<Signal> {
+        self.secret_input = true;
+        self.input_mode = InputMode::Regular;
+        self.editor.isolate_cut_buffer();
+
+        let result = self.read_line(prompt);
+
+        self.editor.zeroize();
+        self.secret_input = false;
+
+        result
+    }
+
     /// Writes `msg` to the terminal with a following carriage return and newline
     fn print_line(&mut self, msg: &str) -> Result<()> {
         self.painter.paint_line(msg)
@@ -369,6 +541,14 @@ impl Reedline {
         Ok(())
     }
 
+    /// Clear the screen and the scrollback of the terminal, so that the prompt or
+    /// other output starts at the first line of an empty terminal.
+    pub fn clear_scrollback(&mut self) -> Result<()> {
+        self.painter.clear_scrollback()?;
+
+        Ok(())
+    }
+
     /// Helper implementing the logic for [`Reedline::read_line()`] to be wrapped
     /// in a `raw_mode` context.
     fn read_line_helper(&mut self, prompt: &dyn Prompt) -> Result<Signal> {
@@ -383,7 +563,17 @@ impl Reedline {
         loop {
             let mut paste_enter_state = false;
 
-            if event::poll(Duration::from_millis(1000))? {
+            let sequence_timeout = self.edit_mode.pending_sequence_timeout();
+            let poll_wait = match sequence_timeout {
+                Some(timeout) => timeout,
+                None if self.highlighter.is_pending() => Duration::from_millis(HIGHLIGHT_POLL_WAIT),
+                None => Duration::from_millis(1000),
+            };
+
+            let repaint_wait = self.repaint_throttle.pending_wait(Instant::now());
+            let poll_wait = repaint_wait.map_or(poll_wait, |wait| wait.min(poll_wait));
+
+            if event::poll(poll_wait)? {
                 let mut latest_resize = None;
 
                 // There could be multiple events queued up!
@@ -442,38 +632,136 @@ impl Reedline {
                 if let Some(ec) = last_edit_commands {
                     reedline_events.push(ReedlineEvent::Edit(ec));
                 }
-            } else if self.animate && !self.painter.exceeds_screen_size() {
+            } else if repaint_wait.is_some() {
+                // The input paused, paint the edits skipped by the repaint throttle
+                self.repaint(prompt)?;
+            } else if sequence_timeout.is_some() {
+                // The next key of a started key sequence didn't arrive in time
+                reedline_events.push(self.edit_mode.flush_pending_sequence());
+            } else if (self.animate || self.highlighter.is_pending())
+                && !self.painter.exceeds_screen_size()
+            {
                 reedline_events.push(ReedlineEvent::Repaint);
             };
 
             for event in reedline_events.drain(..) {
-                match self.handle_event(prompt, event)? {
-                    EventStatus::Exits(signal) => {
-                        // Move the cursor below the input area, for external commands or new read_line call
-                        self.painter.move_cursor_to_end()?;
-                        return Ok(signal);
-                    }
-                    EventStatus::Handled => {
-                        if !paste_enter_state {
-                            self.repaint(prompt)?;
-                        }
-                    }
-                    EventStatus::Inapplicable => {
-                        // Nothing changed, no need to repaint
-                    }
+                if let Some(signal) = self.process_event(prompt, event, !paste_enter_state)? {
+                    return Ok(signal);
+                }
+            }
+        }
+    }
+
+    /// Handles an event of the read loop and repaints if it changed something
+    ///
+    /// Returns the signal if `read_line` has to return.
+    fn process_event(
+        &mut self,
+        prompt: &dyn Prompt,
+        event: ReedlineEvent,
+        repaint: bool,
+    ) -> Result<Option<Signal>> {
+        match self.handle_event(prompt, event)? {
+            EventStatus::Exits(signal) => {
+                // Ctrl-C has already cleared the buffer, keep the last painted input visible
+                if self.repaint_throttle.is_pending() && !matches!(signal, Signal::CtrlC) {
+                    self.repaint(prompt)?;
                 }
+                // Move the cursor below the input area, for external commands or new read_line call
+                self.painter.move_cursor_to_end()?;
+                Ok(Some(signal))
+            }
+            EventStatus::Handled => {
+                if repaint && self.repaint_throttle.request(Instant::now()) {
+                    self.repaint(prompt)?;
+                }
+                Ok(None)
+            }
+            EventStatus::Inapplicable => {
+                // Nothing changed, no need to repaint
+                Ok(None)
             }
         }
     }
 
     fn handle_event(&mut self, prompt: &dyn Prompt, event: ReedlineEvent) -> Result<EventStatus> {
-        if self.input_mode == InputMode::HistorySearch {
+        if self.secret_input {
+            self.handle_secret_event(prompt, event)
+        } else if self.input_mode == InputMode::HistorySearch {
             self.handle_history_search_event(prompt, event)
         } else {
             self.handle_editor_event(prompt, event)
         }
     }
 
+    /// Event handling for [`Reedline::read_secret()`]
+    ///
+    /// Only editing, accepting and cancelling are possible. Everything that could show or
+    /// store the input (history, hints, menus, the external editor) is ignored.
+    fn handle_secret_event(
+        &mut self,
+        prompt: &dyn Prompt,
+        event: ReedlineEvent,
+    ) -> Result<EventStatus> {
+        match event {
+            ReedlineEvent::Edit(commands) => {
+                self.run_edit_commands(&commands);
+                Ok(EventStatus::Handled)
+            }
+            ReedlineEvent::Left => {
+                self.run_edit_commands(&[EditCommand::MoveLeft]);
+                Ok(EventStatus::Handled)
+            }
+            ReedlineEvent::Right => {
+                self.run_edit_commands(&[EditCommand::MoveRight]);
+                Ok(EventStatus::Handled)
+            }
+            ReedlineEvent::Enter => {
+                let secret = self.editor.get_buffer().to_string();
+                self.repaint(prompt)?;
+                self.editor.zeroize();
+                Ok(EventStatus::Exits(Signal::Success(secret)))
+            }
+            ReedlineEvent::CtrlC => {
+                self.editor.zeroize();
+                Ok(EventStatus::Exits(Signal::CtrlC))
+            }
+            ReedlineEvent::CtrlD => {
+                if self.editor.is_empty() {
+                    Ok(EventStatus::Exits(Signal::CtrlD))
+                } else {
+                    self.run_edit_commands(&[EditCommand::Delete]);
+                    Ok(EventStatus::Handled)
+                }
+            }
+            ReedlineEvent::Multiple(events) => {
+                let mut latest_signal = EventStatus::Inapplicable;
+                for event in events {
+                    match self.handle_secret_event(prompt, event)? {
+                        EventStatus::Handled => latest_signal = EventStatus::Handled,
+                        EventStatus::Inapplicable => {}
+                        exits @ EventStatus::Exits(_) => return Ok(exits),
+                    }
+                }
+                Ok(latest_signal)
+            }
+            ReedlineEvent::UntilFound(events) => {
+                for event in events {
+                    match self.handle_secret_event(prompt, event)? {
+                        EventStatus::Inapplicable => {}
+                        success => return Ok(success),
+                    }
+                }
+                Ok(EventStatus::Inapplicable)
+            }
+            ReedlineEvent::Resize(..)
+            | ReedlineEvent::Repaint
+            | ReedlineEvent::ClearScreen
+            | ReedlineEvent::ClearScrollback => self.handle_editor_event(prompt, event),
+            _ => Ok(EventStatus::Inapplicable),
+        }
+    }
+
     fn handle_history_search_event(
         &mut self,
         prompt: &dyn Prompt,
@@ -508,7 +796,14 @@ impl Reedline {
                 self.input_mode = InputMode::Regular;
                 Ok(EventStatus::Exits(Signal::CtrlC))
             }
-            ReedlineEvent::ClearScreen => Ok(EventStatus::Exits(Signal::CtrlL)),
+            ReedlineEvent::ClearScreen => {
+                self.painter.clear_screen()?;
+                Ok(EventStatus::Handled)
+            }
+            ReedlineEvent::ClearScrollback => {
+                self.painter.clear_scrollback()?;
+                Ok(EventStatus::Handled)
+            }
             ReedlineEvent::Enter | ReedlineEvent::HistoryHintComplete => {
                 if let Some(string) = self.history.string_at_cursor() {
                     self.editor.set_buffer(string);
@@ -563,7 +858,9 @@ impl Reedline {
             | ReedlineEvent::MenuLeft
             | ReedlineEvent::MenuRight
             | ReedlineEvent::MenuPageNext
-            | ReedlineEvent::MenuPagePrevious => Ok(EventStatus::Inapplicable),
+            | ReedlineEvent::MenuPagePrevious
+            | ReedlineEvent::MenuToggleSelection
+            | ReedlineEvent::OpenEditor => Ok(EventStatus::Inapplicable),
         }
     }
 
@@ -662,6 +959,13 @@ impl Reedline {
                         Ok(EventStatus::Handled)
                     })
             }
+            ReedlineEvent::MenuToggleSelection => match self.active_menu() {
+                Some(menu) if menu.multi_select() => {
+                    menu.menu_event(MenuEvent::ToggleSelection);
+                    Ok(EventStatus::Handled)
+                }
+                _ => Ok(EventStatus::Inapplicable),
+            },
             ReedlineEvent::HistoryHintComplete => {
                 let current_hint = self.hinter.complete_hint();
                 if self.hints_active()
@@ -717,11 +1021,27 @@ impl Reedline {
                 self.editor.reset_undo_stack();
                 Ok(EventStatus::Exits(Signal::CtrlC))
             }
+            ReedlineEvent::OpenEditor => {
+                // The editor needs the terminal in its regular mode
+                terminal::disable_raw_mode()?;
+                self.run_external_editor();
+                terminal::enable_raw_mode()?;
+                self.painter.initialize_prompt_position()?;
+                Ok(EventStatus::Handled)
+            }
             ReedlineEvent::ClearScreen => {
                 self.menus
                     .iter_mut()
                     .for_each(|menu| menu.menu_event(MenuEvent::Deactivate));
-                Ok(EventStatus::Exits(Signal::CtrlL))
+                self.painter.clear_screen()?;
+                Ok(EventStatus::Handled)
+            }
+            ReedlineEvent::ClearScrollback => {
+                self.menus
+                    .iter_mut()
+                    .for_each(|menu| menu.menu_event(MenuEvent::Deactivate));
+                self.painter.clear_scrollback()?;
+                Ok(EventStatus::Handled)
             }
             ReedlineEvent::Enter => {
                 for menu in self.menus.iter_mut() {
@@ -742,7 +1062,7 @@ impl Reedline {
                 if matches!(self.validator.validate(&buffer), ValidationResult::Complete) {
                     self.hide_hints = true;
                     // Additional repaint to show the content without hints etc.
-                    self.repaint(prompt)?;
+                    self.final_paint(prompt)?;
                     self.history.append(self.editor.get_buffer());
                     self.run_edit_commands(&[EditCommand::Clear]);
                     self.editor.reset_undo_stack();
@@ -992,6 +1312,17 @@ impl Reedline {
         }
     }
 
+    /// Replaces the buffer with the result of editing it in the external editor
+    ///
+    /// The original buffer is kept if the editor fails or leaves an empty file.
+    fn run_external_editor(&mut self) {
+        if let Ok(Some(buffer)) = self.external_editor.edit(self.editor.get_buffer()) {
+            self.editor.set_buffer(buffer);
+            self.editor.line_buffer().move_to_end();
+            self.editor.remember_undo_state(true);
+        }
+    }
+
     /// Executes [`EditCommand`] actions by modifying the internal state appropriately. Does not output itself.
     fn run_edit_commands(&mut self, commands: &[EditCommand]) {
         if self.input_mode == InputMode::HistoryTraversal {
@@ -1039,14 +1370,40 @@ impl Reedline {
 
     /// Repaint of either the buffer or the parts for reverse history search
     fn repaint(&mut self, prompt: &dyn Prompt) -> io::Result<()> {
+        self.repaint_throttle.painted(Instant::now());
+
         // Repainting
-        if self.input_mode == InputMode::HistorySearch {
+        if self.secret_input {
+            self.secret_paint(prompt)
+        } else if self.input_mode == InputMode::HistorySearch {
             self.history_search_paint(prompt)
         } else {
             self.buffer_paint(prompt)
         }
     }
 
+    /// Paints the accepted line, using the transient prompt if the prompt provides one
+    fn final_paint(&mut self, prompt: &dyn Prompt) -> io::Result<()> {
+        let buffer_to_paint = self.editor.get_buffer();
+        let (input, _) = self
+            .highlighter
+            .highlight(buffer_to_paint, buffer_to_paint.len())
+            .render_around_insertion_point(
+                buffer_to_paint.len(),
+                prompt.render_prompt_multiline_indicator().borrow(),
+                self.use_ansi_coloring,
+            );
+
+        match PromptLines::transient(prompt, &input) {
+            Some(lines) => {
+                self.repaint_throttle.painted(Instant::now());
+                self.painter
+                    .repaint_buffer(prompt, &lines, None, None, self.use_ansi_coloring)
+            }
+            None => self.repaint(prompt),
+        }
+    }
+
     #[cfg(feature = "bashisms")]
     /// Parses the ! command to replace entries from the history
     fn parse_bang_command(&mut self) -> Option<ReedlineEvent> {
@@ -1112,15 +1469,7 @@ impl Reedline {
             let prompt_history_search = PromptHistorySearch::new(status, substring.clone());
 
             let res_string = self.history.string_at_cursor().unwrap_or_default();
-
-            // Highlight matches
-            let res_string = if self.use_ansi_coloring {
-                let match_highlighter = SimpleMatchHighlighter::new(substring);
-                let styled = match_highlighter.highlight(&res_string, 0);
-                styled.render_simple()
-            } else {
-                res_string
-            };
+            let res_string = self.highlight_history_search(substring, res_string);
 
             let lines = PromptLines::new(
                 prompt,
@@ -1131,13 +1480,57 @@ impl Reedline {
                 "",
             );
 
-            self.painter
-                .repaint_buffer(prompt, &lines, None, self.use_ansi_coloring)?;
+            let cursor_shape = self.cursor_config.shape_for(&self.prompt_edit_mode());
+
+            self.painter.repaint_buffer(
+                prompt,
+                &lines,
+                None,
+                cursor_shape,
+                self.use_ansi_coloring,
+            )?;
         }
 
         Ok(())
     }
 
+    /// Highlights the matches of the search in the found entry, if ansi coloring is used
+    fn highlight_history_search(&self, substring: String, res_string: String) -> String {
+        if self.use_ansi_coloring {
+            SimpleMatchHighlighter::new(substring)
+                .with_match_style(self.history_search_style.match_style)
+                .with_neutral_style(self.history_search_style.text_style)
+                .highlight(&res_string, 0)
+                .render_simple()
+        } else {
+            res_string
+        }
+    }
+
+    /// Paints the prompt with the mask in place of the secret input
+    fn secret_paint(&mut self, prompt: &dyn Prompt) -> Result<()> {
+        let buffer = self.editor.get_buffer();
+        let (before_cursor, after_cursor) = buffer.split_at(self.editor.insertion_point());
+        let mask = |text: &str| match self.secret_mask {
+            Some(mask) => mask.to_string().repeat(text.graphemes(true).count()),
+            None => String::new(),
+        };
+        let (before_cursor, after_cursor) = (mask(before_cursor), mask(after_cursor));
+
+        let lines = PromptLines::new(
+            prompt,
+            self.prompt_edit_mode(),
+            None,
+            &before_cursor,
+            &after_cursor,
+            "",
+        );
+        let cursor_shape = self.cursor_config.shape_for(&self.prompt_edit_mode());
+
+        self.painter
+            .repaint_buffer(prompt, &lines, None, cursor_shape, self.use_ansi_coloring)
+    }
+
     /// Triggers a full repaint including the prompt parts
     ///
     /// Includes the highlighting and hinting calls.
@@ -1145,12 +1538,19 @@ impl Reedline {
         let cursor_position_in_buffer = self.editor.insertion_point();
         let buffer_to_paint = self.editor.get_buffer();
 
+        let multiline_prompts = continuation_indicators(
+            self.validator.as_ref(),
+            buffer_to_paint,
+            prompt.render_prompt_multiline_indicator().borrow(),
+        );
+        let multiline_prompts: Vec<&str> = multiline_prompts.iter().map(String::as_str).collect();
+
         let (before_cursor, after_cursor) = self
             .highlighter
             .highlight(buffer_to_paint, cursor_position_in_buffer)
-            .render_around_insertion_point(
+            .render_around_insertion_point_with_prompts(
                 cursor_position_in_buffer,
-                prompt.render_prompt_multiline_indicator().borrow(),
+                &multiline_prompts,
                 self.use_ansi_coloring,
             );
 
@@ -1190,9 +1590,362 @@ impl Reedline {
         }
 
         let menu = self.menus.iter().find(|menu| menu.is_active());
+        let cursor_shape = self.cursor_config.shape_for(&self.prompt_edit_mode());
 
         self.painter
-            .repaint_buffer(prompt, &lines, menu, self.use_ansi_coloring)
+            .repaint_buffer(prompt, &lines, menu, cursor_shape, self.use_ansi_coloring)
+    }
+}
+
+/// Continuation prompt for every line after the first one of `buffer`
+///
+/// The validator can replace the `multiline_indicator` of the prompt based on the input
+/// before the line, e.g. to show that a quote or a bracket is still open.
+fn continuation_indicators(
+    validator: &dyn Validator,
+    buffer: &str,
+    multiline_indicator: &str,
+) -> Vec<String> {
+    buffer
+        .match_indices('\n')
+        .map(|(index, _)| {
+            validator
+                .continuation_indicator(&buffer[..index])
+                .unwrap_or_else(|| multiline_indicator.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn undo_stack_can_be_inspected() {
+        let mut line_editor = Reedline::create();
+        line_editor.run_edit_commands(&[EditCommand::InsertString("ls".to_string())]);
+        line_editor.run_edit_commands(&[EditCommand::InsertString(" -la".to_string())]);
+
+        assert_eq!(line_editor.undo_stack_len(), 3);
+        assert_eq!(
+            line_editor.peek_undo().map(LineBuffer::get_buffer),
+            Some("ls")
+        );
+    }
+
+    #[test]
+    fn continuation_indicators_follow_the_validator() {
+        struct QuoteValidator;
+
+        impl Validator for QuoteValidator {
+            fn validate(&self, _line: &str) -> ValidationResult {
+                ValidationResult::Incomplete
+            }
+
+            fn continuation_indicator(&self, previous_input: &str) -> Option<String> {
+                if previous_input.matches('"').count() % 2 == 1 {
+                    Some("dquote> ".to_string())
+                } else if previous_input.matches('{').count() > previous_input.matches('}').count()
+                {
+                    Some("brace> ".to_string())
+                } else {
+                    None
+                }
+            }
+        }
+
+        assert_eq!(
+            continuation_indicators(&QuoteValidator, "if x {\n  echo \"a\n b\"\n}", "::: "),
+            vec!["brace> ", "dquote> ", "brace> "]
+        );
+        assert_eq!(
+            continuation_indicators(&DefaultValidator, "{\n\"", "::: "),
+            vec!["::: "]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn external_editor_replaces_buffer() {
+        use std::{fs, os::unix::fs::PermissionsExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("editor.sh");
+        fs::write(&script, "#!/bin/sh\necho \" | wc -l\" >> \"$1\"\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        std::env::set_var("REEDLINE_TEST_ENGINE_EDITOR", &script);
+
+        let mut line_editor = Reedline::create().with_external_editor(
+            ExternalEditor::default().with_env_vars(vec!["REEDLINE_TEST_ENGINE_EDITOR".into()]),
+        );
+        line_editor.run_edit_commands(&[EditCommand::InsertString("ls".to_string())]);
+        line_editor.run_external_editor();
+
+        assert_eq!(line_editor.editor.get_buffer(), "ls | wc -l");
+        assert_eq!(line_editor.editor.line_buffer().insertion_point(), 10);
+
+        std::env::set_var("REEDLINE_TEST_ENGINE_EDITOR", dir.path().join("missing"));
+        line_editor.run_external_editor();
+
+        assert_eq!(line_editor.editor.get_buffer(), "ls | wc -l");
+    }
+
+    #[test]
+    fn burst_of_edits_is_painted_once_input_pauses() {
+        use crate::{painting::SharedBuffer, DefaultPrompt};
+
+        let output = SharedBuffer::default();
+        let mut line_editor = Reedline::create().with_repaint_interval(Duration::from_secs(60));
+        line_editor.painter = Painter::from_writer(output.clone());
+        line_editor.painter.handle_resize(80, 24);
+        let prompt = DefaultPrompt::default();
+
+        let keystrokes = "abcdefghij";
+        for c in keystrokes.chars() {
+            let event = ReedlineEvent::Edit(vec![EditCommand::InsertChar(c)]);
+            assert!(line_editor
+                .process_event(&prompt, event, true)
+                .unwrap()
+                .is_none());
+        }
+
+        let paints = output.take().matches("\x1b[?25l").count();
+        assert!(paints < keystrokes.len());
+        assert!(line_editor.repaint_throttle.is_pending());
+
+        // What the read loop does once no further input arrives
+        line_editor.repaint(&prompt).unwrap();
+
+        assert!(output.take().contains(keystrokes));
+        assert!(!line_editor.repaint_throttle.is_pending());
+    }
+
+    #[test]
+    fn cursor_shape_is_restored_when_read_line_returns() {
+        use crate::{painting::SharedBuffer, DefaultPrompt, Vi};
+
+        let output = SharedBuffer::default();
+        let mut line_editor = Reedline::create()
+            .with_edit_mode(Box::new(Vi::default()))
+            .with_cursor_config(CursorConfig::vi_style());
+        line_editor.painter = Painter::from_writer(output.clone());
+        line_editor.painter.handle_resize(80, 24);
+        let prompt = DefaultPrompt::default();
+
+        for exit in [ReedlineEvent::Enter, ReedlineEvent::CtrlC] {
+            let event = ReedlineEvent::Edit(vec![EditCommand::InsertChar('a')]);
+            line_editor.process_event(&prompt, event, true).unwrap();
+            assert!(output.take().contains("\x1b[5 q"));
+
+            let signal = line_editor.process_event(&prompt, exit, true).unwrap();
+            // What read_line does once the read loop returned
+            assert!(line_editor.finish_read_line(Ok(signal.unwrap())).is_ok());
+            assert!(output.take().ends_with("\x1b[0 q"));
+        }
+
+        let event = ReedlineEvent::Edit(vec![EditCommand::InsertChar('a')]);
+        line_editor.process_event(&prompt, event, true).unwrap();
+        output.take();
+
+        let error = std::io::Error::other("terminal is gone");
+        assert!(line_editor.finish_read_line(Err(error)).is_err());
+        assert_eq!(output.take(), "\x1b[0 q");
+    }
+
+    #[test]
+    fn secret_input_is_neither_painted_nor_stored() {
+        use crate::{painting::SharedBuffer, DefaultPrompt};
+
+        let output = SharedBuffer::default();
+        let mut line_editor = Reedline::create();
+        line_editor.painter = Painter::from_writer(output.clone());
+        line_editor.painter.handle_resize(80, 24);
+        let prompt = DefaultPrompt::default();
+        line_editor.secret_input = true;
+
+        let events = vec![
+            ReedlineEvent::Edit(vec![EditCommand::InsertString("hunter2".to_string())]),
+            ReedlineEvent::Up,
+            ReedlineEvent::OpenEditor,
+            ReedlineEvent::Enter,
+        ];
+        let mut signal = None;
+        for event in events {
+            signal = line_editor.process_event(&prompt, event, true).unwrap();
+        }
+
+        assert!(matches!(signal, Some(Signal::Success(secret)) if secret == "hunter2"));
+        assert!(!output.take().contains("hunter"));
+        assert_eq!(line_editor.history.iter_chronologic().count(), 0);
+        assert!(line_editor.editor.is_empty());
+        assert_eq!(line_editor.undo_stack_len(), 1);
+    }
+
+    #[test]
+    fn secret_cuts_cannot_be_pasted_later() {
+        use crate::{painting::SharedBuffer, DefaultPrompt};
+
+        let mut line_editor = Reedline::create();
+        line_editor.painter = Painter::from_writer(SharedBuffer::default());
+        line_editor.painter.handle_resize(80, 24);
+        let prompt = DefaultPrompt::default();
+        line_editor.secret_input = true;
+        line_editor.editor.isolate_cut_buffer();
+
+        let events = vec![
+            ReedlineEvent::Edit(vec![EditCommand::InsertString("hunter2".to_string())]),
+            ReedlineEvent::Edit(vec![EditCommand::CutFromStart]),
+            ReedlineEvent::Edit(vec![EditCommand::InsertString("swordfish".to_string())]),
+            ReedlineEvent::Enter,
+        ];
+        for event in events {
+            line_editor.process_event(&prompt, event, false).unwrap();
+        }
+        line_editor.secret_input = false;
+
+        let paste = ReedlineEvent::Edit(vec![EditCommand::PasteCutBufferBefore]);
+        line_editor.process_event(&prompt, paste, false).unwrap();
+        assert!(line_editor.editor.is_empty());
+    }
+
+    #[test]
+    fn secret_mask_replaces_typed_characters() {
+        use crate::{painting::SharedBuffer, DefaultPrompt};
+
+        let output = SharedBuffer::default();
+        let mut line_editor = Reedline::create().with_secret_mask(Some('*'));
+        line_editor.painter = Painter::from_writer(output.clone());
+        line_editor.painter.handle_resize(80, 24);
+        line_editor.secret_input = true;
+
+        let event = ReedlineEvent::Edit(vec![EditCommand::InsertString("pässwort".to_string())]);
+        line_editor
+            .process_event(&DefaultPrompt::default(), event, true)
+            .unwrap();
+
+        let painted = output.take();
+        assert!(painted.contains("********"));
+        assert!(!painted.contains("*********"));
+    }
+
+    #[test]
+    fn accepted_line_is_painted_with_the_transient_prompt() {
+        use crate::painting::SharedBuffer;
+        use std::borrow::Cow;
+
+        struct TransientPrompt;
+
+        impl Prompt for TransientPrompt {
+            fn render_prompt_left(&self) -> Cow<'_, str> {
+                "~/projects/reedline\non main".into()
+            }
+
+            fn render_prompt_right(&self) -> Cow<'_, str> {
+                "12:00".into()
+            }
+
+            fn render_prompt_indicator(&self, _prompt_mode: PromptEditMode) -> Cow<'_, str> {
+                "> ".into()
+            }
+
+            fn render_prompt_multiline_indicator(&self) -> Cow<'_, str> {
+                "::: ".into()
+            }
+
+            fn render_prompt_history_search_indicator(
+                &self,
+                _history_search: PromptHistorySearch,
+            ) -> Cow<'_, str> {
+                "? ".into()
+            }
+
+            fn render_prompt_transient(&self) -> Option<Cow<'_, str>> {
+                Some("$ ".into())
+            }
+        }
+
+        let output = SharedBuffer::default();
+        let mut line_editor = Reedline::create().with_ansi_colors(false);
+        line_editor.painter = Painter::from_writer(output.clone());
+        line_editor.painter.handle_resize(80, 24);
+
+        let event = ReedlineEvent::Edit(vec![EditCommand::InsertString("ls -la".to_string())]);
+        line_editor
+            .process_event(&TransientPrompt, event, true)
+            .unwrap();
+        assert!(output.take().contains("on main"));
+
+        line_editor
+            .process_event(&TransientPrompt, ReedlineEvent::Enter, true)
+            .unwrap();
+        let painted = output.take();
+        let prompt_start = painted.find("$ ").unwrap();
+        assert!(painted[prompt_start..].contains("ls -la"));
+        assert!(!painted.contains("on main"));
+        assert!(!painted.contains("12:00"));
+        assert!(!painted.contains("> "));
+    }
+
+    #[test]
+    fn history_search_style_is_applied_to_matches() {
+        use nu_ansi_term::{Color, Style};
+
+        let match_style = Color::Black.on(Color::Yellow);
+        let text_style = Style::new().italic();
+        let line_editor = Reedline::create().with_history_search_style(HistorySearchStyle {
+            match_style,
+            text_style,
+        });
+
+        assert_eq!(
+            line_editor.highlight_history_search("ls".to_string(), "ls -la; ls".to_string()),
+            format!(
+                "{}{}{}",
+                match_style.paint("ls"),
+                text_style.paint(" -la; "),
+                match_style.paint("ls")
+            )
+        );
+
+        let line_editor = line_editor.with_ansi_colors(false);
+        assert_eq!(
+            line_editor.highlight_history_search("ls".to_string(), "ls -la".to_string()),
+            "ls -la"
+        );
+    }
+
+    #[test]
+    fn space_toggles_selection_of_multi_select_menu() {
+        use crate::{painting::SharedBuffer, CompletionMenu, DefaultPrompt};
+
+        let completer = DefaultCompleter::new(vec!["foobar".into(), "food".into(), "bar".into()]);
+        let mut line_editor = Reedline::create().with_menu(
+            Box::new(CompletionMenu::default().with_multi_select(true)),
+            Some(Box::new(completer)),
+        );
+        line_editor.painter = Painter::from_writer(SharedBuffer::default());
+        line_editor.painter.handle_resize(80, 24);
+        let prompt = DefaultPrompt;
+        let space = Event::Key(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+
+        let events = vec![
+            ReedlineEvent::Edit(vec![EditCommand::InsertString("cat fo".to_string())]),
+            ReedlineEvent::Menu("completion_menu".to_string()),
+            line_editor.edit_mode.parse_event(space),
+            ReedlineEvent::MenuNext,
+            line_editor.edit_mode.parse_event(space),
+            ReedlineEvent::Enter,
+        ];
+        for event in events {
+            line_editor.process_event(&prompt, event, true).unwrap();
+        }
+        assert_eq!(line_editor.editor.get_buffer(), "cat foobar food");
+
+        // Without an open menu the space is typed
+        let event = line_editor.edit_mode.parse_event(space);
+        line_editor.process_event(&prompt, event, true).unwrap();
+        assert_eq!(line_editor.editor.get_buffer(), "cat foobar food ");
     }
 }
 
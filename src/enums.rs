@@ -11,6 +11,12 @@ pub enum Signal {
     /// Abort with `Ctrl+D` signalling `EOF` or abort of a whole interactive session
     CtrlD, // End terminal session
     /// Signal to clear the current screen. Buffer content remains untouched.
+    ///
+    /// The engine clears the screen itself for [`ReedlineEvent::ClearScreen`] and
+    /// [`ReedlineEvent::ClearScrollback`] and does not return this signal anymore.
+    #[deprecated(
+        note = "never returned: the engine clears the screen itself for ReedlineEvent::ClearScreen"
+    )]
     CtrlL, // FormFeed/Clear current screen
 }
 
@@ -100,6 +106,9 @@ pub enum EditCommand {
     /// Paste the cut buffer in front of the insertion point (vi `p`)
     PasteCutBufferAfter,
 
+    /// Replace the text just pasted with the next older entry of the kill ring (Emacs `M-y`)
+    YankPop,
+
     /// Upper case the current word
     UppercaseWord,
 
@@ -187,6 +196,7 @@ impl EditCommand {
             | EditCommand::CutWordRight
             | EditCommand::PasteCutBufferBefore
             | EditCommand::PasteCutBufferAfter
+            | EditCommand::YankPop
             | EditCommand::UppercaseWord
             | EditCommand::LowercaseWord
             | EditCommand::CapitalizeChar
@@ -203,6 +213,7 @@ impl EditCommand {
 }
 
 /// Specifies how the (previously executed) operation should be treated in the Undo stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UndoBehavior {
     /// Operation is not affecting the LineBuffers content and should be ignored
     ///
@@ -212,7 +223,8 @@ pub enum UndoBehavior {
     Full,
     /// The operation is a single operation that should be best coalesced in logical units such as words
     ///
-    /// e.g. insertion of characters by typing
+    /// e.g. insertion of characters by typing. Only consecutive coalescing operations are grouped,
+    /// any other operation starts a new undo step.
     Coalesce,
 }
 
@@ -250,8 +262,13 @@ pub enum ReedlineEvent {
     CtrlC,
 
     /// Clears the screen and sets prompt to first line
+    ///
+    /// The content of the screen is kept in the scrollback of the terminal
     ClearScreen,
 
+    /// Clears the screen and the scrollback of the terminal and sets prompt to first line
+    ClearScrollback,
+
     /// Handle enter event
     Enter,
 
@@ -291,6 +308,10 @@ pub enum ReedlineEvent {
     /// Search the history for a string
     SearchHistory,
 
+    /// Edit the buffer in the editor set by `$VISUAL` or `$EDITOR`
+    /// (see [`crate::ExternalEditor`])
+    OpenEditor,
+
     /// In vi mode multiple reedline events can be chained while parsing the
     /// command or movement characters
     Multiple(Vec<ReedlineEvent>),
@@ -325,6 +346,9 @@ pub enum ReedlineEvent {
     /// Move to the previous history page
     MenuPagePrevious,
 
+    /// Select or unselect the current entry of a menu allowing several selected entries
+    MenuToggleSelection,
+
     /// Way to bind the execution of a whole command (directly returning from [`crate::Reedline::read_line()`]) to a keybinding
     ExecuteHostCommand(String),
 }
@@ -0,0 +1,239 @@
+use crate::highlighter::Highlighter;
+use crate::StyledText;
+use nu_ansi_term::Style;
+use std::sync::{
+    mpsc::{channel, Receiver, Sender},
+    Arc, Mutex,
+};
+
+#[derive(Default)]
+struct HighlightState {
+    /// Line most recently requested by the engine
+    line: String,
+    /// Newest result that was pushed for the requested line
+    last_good: Option<(String, StyledText)>,
+    /// The result for `line` has not arrived yet
+    pending: bool,
+}
+
+/// Highlighter whose styling is computed by the host, e.g. on another thread
+///
+/// Every new line is sent as a request to the [`HighlightRequests`] handle. Until the
+/// result for it is pushed back, the styling of the last result is kept for the
+/// unchanged start of the line and the changed rest is shown unstyled.
+///
+/// # Example
+/// ```rust,no_run
+/// use reedline::{AsyncHighlighter, ExampleHighlighter, Highlighter, Reedline};
+///
+/// let (highlighter, requests) = AsyncHighlighter::new();
+/// std::thread::spawn(move || {
+///     let expensive_highlighter = ExampleHighlighter::default();
+///     while let Some(line) = requests.next() {
+///         requests.push(&line, expensive_highlighter.highlight(&line, line.len()));
+///     }
+/// });
+///
+/// let mut line_editor = Reedline::create().with_highlighter(Box::new(highlighter));
+/// ```
+pub struct AsyncHighlighter {
+    state: Arc<Mutex<HighlightState>>,
+    requests: Sender<String>,
+}
+
+/// Host side of an [`AsyncHighlighter`] receiving the lines to highlight
+pub struct HighlightRequests {
+    state: Arc<Mutex<HighlightState>>,
+    requests: Receiver<String>,
+}
+
+impl AsyncHighlighter {
+    /// Creates the highlighter for the engine and the handle used by the host to answer it
+    pub fn new() -> (Self, HighlightRequests) {
+        let state = Arc::new(Mutex::new(HighlightState::default()));
+        let (sender, receiver) = channel();
+
+        (
+            Self {
+                state: state.clone(),
+                requests: sender,
+            },
+            HighlightRequests {
+                state,
+                requests: receiver,
+            },
+        )
+    }
+}
+
+impl Highlighter for AsyncHighlighter {
+    fn highlight(&self, line: &str, _cursor: usize) -> StyledText {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        if let Some((highlighted_line, styled_text)) = &state.last_good {
+            if highlighted_line == line {
+                let styled_text = styled_text.clone();
+                state.pending = false;
+                return styled_text;
+            }
+        }
+
+        if state.line != line || !state.pending {
+            state.line = line.to_string();
+            state.pending = true;
+            // Without a receiver the line simply stays unstyled
+            let _ = self.requests.send(line.to_string());
+        }
+
+        match &state.last_good {
+            Some((highlighted_line, styled_text)) => {
+                keep_unchanged_styles(highlighted_line, styled_text, line)
+            }
+            None => unstyled(line),
+        }
+    }
+
+    fn is_pending(&self) -> bool {
+        matches!(self.state.lock(), Ok(state) if state.pending)
+    }
+}
+
+impl HighlightRequests {
+    /// Blocks until a line has to be highlighted
+    ///
+    /// Requests queued in the meantime are skipped, only the newest line is returned.
+    /// Returns `None` once the [`AsyncHighlighter`] has been dropped.
+    pub fn next(&self) -> Option<String> {
+        let line = self.requests.recv().ok()?;
+        Some(self.requests.try_iter().last().unwrap_or(line))
+    }
+
+    /// Hands back the styling of `line`
+    ///
+    /// Results for lines that have been edited since the request are dropped and `false`
+    /// is returned.
+    pub fn push(&self, line: &str, styled_text: StyledText) -> bool {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        if state.line == line {
+            state.last_good = Some((line.to_string(), styled_text));
+            true
+        } else {
+            false
+        }
+    }
+}
+
+fn unstyled(line: &str) -> StyledText {
+    let mut styled_text = StyledText::new();
+    if !line.is_empty() {
+        styled_text.push((Style::new(), line.to_string()));
+    }
+    styled_text
+}
+
+/// Styling of `highlighted_line` applied to the common start with `line`, the rest is unstyled
+fn keep_unchanged_styles(
+    highlighted_line: &str,
+    highlighted: &StyledText,
+    line: &str,
+) -> StyledText {
+    let common = highlighted_line
+        .char_indices()
+        .zip(line.chars())
+        .find(|((_, old), new)| old != new)
+        .map_or_else(
+            || highlighted_line.len().min(line.len()),
+            |((index, _), _)| index,
+        );
+
+    let mut styled_text = StyledText::new();
+    let mut offset = 0;
+    for (style, text) in &highlighted.buffer {
+        if offset >= common {
+            break;
+        }
+        let end = (offset + text.len()).min(common);
+        styled_text.push((*style, text[..end - offset].to_string()));
+        offset += text.len();
+    }
+
+    let rest = unstyled(&line[common..]);
+    styled_text.buffer.extend(rest.buffer);
+    styled_text
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::painting::styled;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn line_stays_unstyled_until_result_arrives() {
+        let (highlighter, requests) = AsyncHighlighter::new();
+
+        assert_eq!(
+            highlighter.highlight("ls", 2),
+            styled(&[(Style::new(), "ls")])
+        );
+        assert!(highlighter.is_pending());
+        assert_eq!(requests.next(), Some("ls".to_string()));
+
+        let bold = Style::new().bold();
+        assert!(requests.push("ls", styled(&[(bold, "ls")])));
+        assert_eq!(highlighter.highlight("ls", 2), styled(&[(bold, "ls")]));
+        assert!(!highlighter.is_pending());
+    }
+
+    #[test]
+    fn delayed_results_leave_no_stale_spans() {
+        let (highlighter, requests) = AsyncHighlighter::new();
+        let bold = Style::new().bold();
+        let red = Style::new().fg(nu_ansi_term::Color::Red);
+
+        highlighter.highlight("ls -la", 6);
+        requests.push("ls -la", styled(&[(bold, "ls"), (red, " -la")]));
+        highlighter.highlight("ls -la", 6);
+
+        // The edit is requested and the last good styling is kept for the unchanged start
+        assert_eq!(
+            highlighter.highlight("ls -a", 5),
+            styled(&[(bold, "ls"), (red, " -"), (Style::new(), "a")])
+        );
+        highlighter.highlight("ls -ab", 6);
+        // Only the newest request is handed out
+        assert_eq!(requests.next(), Some("ls -ab".to_string()));
+
+        // A slow result for an outdated line is dropped
+        assert!(!requests.push("ls -a", styled(&[(bold, "ls -a")])));
+        assert_eq!(
+            highlighter.highlight("ls -ab", 6),
+            styled(&[(bold, "ls"), (red, " -"), (Style::new(), "ab")])
+        );
+
+        assert!(requests.push("ls -ab", styled(&[(bold, "ls"), (Style::new(), " -ab")])));
+        assert_eq!(
+            highlighter.highlight("ls -ab", 6),
+            styled(&[(bold, "ls"), (Style::new(), " -ab")])
+        );
+    }
+
+    #[test]
+    fn deleted_text_drops_its_styling() {
+        let (highlighter, requests) = AsyncHighlighter::new();
+        let bold = Style::new().bold();
+
+        highlighter.highlight("ls -la", 6);
+        requests.push("ls -la", styled(&[(bold, "ls -la")]));
+
+        assert_eq!(highlighter.highlight("ls", 2), styled(&[(bold, "ls")]));
+        assert_eq!(highlighter.highlight("", 0), styled(&[]));
+    }
+}
@@ -0,0 +1,287 @@
+use crate::highlighter::Highlighter;
+use crate::StyledText;
+use nu_ansi_term::{Color, Style};
+use std::ops::Range;
+
+/// Highlights the bracket matching the one at the cursor and flags unmatched brackets
+///
+/// Wraps another highlighter whose styling is kept for all other text. Round, square and
+/// curly brackets are matched, brackets inside quoted strings are skipped.
+///
+/// Default style:
+///
+/// - bracket pair at the cursor: Cyan bold
+/// - unmatched brackets: Red bold
+///
+/// # Example
+/// ```rust,no_run
+/// use reedline::{BracketHighlighter, ExampleHighlighter, Reedline};
+///
+/// let highlighter = BracketHighlighter::new(Box::new(ExampleHighlighter::default()));
+/// let mut line_editor = Reedline::create().with_highlighter(Box::new(highlighter));
+/// ```
+pub struct BracketHighlighter {
+    highlighter: Option<Box<dyn Highlighter>>,
+    match_style: Style,
+    unmatched_style: Style,
+}
+
+impl Default for BracketHighlighter {
+    fn default() -> Self {
+        Self {
+            highlighter: None,
+            match_style: Color::Cyan.bold(),
+            unmatched_style: Color::Red.bold(),
+        }
+    }
+}
+
+impl BracketHighlighter {
+    /// Adds bracket highlighting on top of `highlighter`
+    pub fn new(highlighter: Box<dyn Highlighter>) -> Self {
+        Self {
+            highlighter: Some(highlighter),
+            ..Self::default()
+        }
+    }
+
+    /// Set style for the bracket at the cursor and its partner
+    #[must_use]
+    pub fn with_match_style(mut self, match_style: Style) -> Self {
+        self.match_style = match_style;
+        self
+    }
+
+    /// Set style for brackets without a partner
+    #[must_use]
+    pub fn with_unmatched_style(mut self, unmatched_style: Style) -> Self {
+        self.unmatched_style = unmatched_style;
+        self
+    }
+}
+
+impl Highlighter for BracketHighlighter {
+    fn highlight(&self, line: &str, cursor: usize) -> StyledText {
+        let styled_text = match &self.highlighter {
+            Some(highlighter) => highlighter.highlight(line, cursor),
+            None => {
+                let mut styled_text = StyledText::new();
+                if !line.is_empty() {
+                    styled_text.push((Style::new(), line.to_string()));
+                }
+                styled_text
+            }
+        };
+
+        let brackets = Brackets::parse(line);
+        let mut styles: Vec<(Range<usize>, Style)> = brackets
+            .unmatched
+            .iter()
+            .map(|&index| (index..index + 1, self.unmatched_style))
+            .collect();
+
+        // A bracket under the cursor takes precedence over the one in front of it
+        let pair = [Some(cursor), cursor.checked_sub(1)]
+            .into_iter()
+            .flatten()
+            .find_map(|index| brackets.partner(index).map(|partner| (index, partner)));
+        if let Some((index, partner)) = pair {
+            styles.push((index..index + 1, self.match_style));
+            styles.push((partner..partner + 1, self.match_style));
+        }
+
+        restyle(styled_text, styles)
+    }
+
+    fn is_pending(&self) -> bool {
+        matches!(&self.highlighter, Some(highlighter) if highlighter.is_pending())
+    }
+}
+
+/// Byte positions of the brackets in a line
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Brackets {
+    pairs: Vec<(usize, usize)>,
+    unmatched: Vec<usize>,
+}
+
+impl Brackets {
+    fn parse(line: &str) -> Self {
+        let mut brackets = Brackets::default();
+        let mut open: Vec<(usize, char)> = Vec::new();
+        let mut quote: Option<char> = None;
+        let mut escaped = false;
+
+        for (index, c) in line.char_indices() {
+            if let Some(quote_char) = quote {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == quote_char {
+                    quote = None;
+                }
+                continue;
+            }
+
+            match c {
+                '"' | '\'' | '`' => quote = Some(c),
+                '(' | '[' | '{' => open.push((index, c)),
+                ')' | ']' | '}' => match open.last() {
+                    Some(&(open_index, open_char)) if closing(open_char) == c => {
+                        open.pop();
+                        brackets.pairs.push((open_index, index));
+                    }
+                    _ => brackets.unmatched.push(index),
+                },
+                _ => {}
+            }
+        }
+
+        brackets
+            .unmatched
+            .extend(open.into_iter().map(|(index, _)| index));
+        brackets.unmatched.sort_unstable();
+        brackets
+    }
+
+    fn partner(&self, index: usize) -> Option<usize> {
+        self.pairs.iter().find_map(|&(open, close)| {
+            if open == index {
+                Some(close)
+            } else if close == index {
+                Some(open)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+fn closing(open: char) -> char {
+    match open {
+        '(' => ')',
+        '[' => ']',
+        _ => '}',
+    }
+}
+
+/// Applies `styles` to the byte ranges of `styled_text`, splitting its parts where needed
+fn restyle(styled_text: StyledText, styles: Vec<(Range<usize>, Style)>) -> StyledText {
+    if styles.is_empty() {
+        return styled_text;
+    }
+
+    let mut restyled = StyledText::new();
+    let mut offset = 0;
+    for (style, text) in styled_text.buffer {
+        let mut start = 0;
+        while start < text.len() {
+            let position = offset + start;
+            let (end, part_style) = match styles.iter().find(|(range, _)| range.contains(&position))
+            {
+                Some((range, new_style)) => (range.end - offset, *new_style),
+                None => {
+                    let next = styles
+                        .iter()
+                        .map(|(range, _)| range.start)
+                        .filter(|&start| start > position)
+                        .min()
+                        .map_or(text.len(), |start| start - offset);
+                    (next, style)
+                }
+            };
+            let end = end.min(text.len());
+            restyled.push((part_style, text[start..end].to_string()));
+            start = end;
+        }
+        offset += text.len();
+    }
+
+    restyled
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::painting::styled;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn bracket_before_cursor_highlights_its_partner() {
+        let highlighter = BracketHighlighter::default();
+        let matched = Color::Cyan.bold();
+
+        assert_eq!(
+            highlighter.highlight("echo (ls)", 9),
+            styled(&[
+                (Style::new(), "echo "),
+                (matched, "("),
+                (Style::new(), "ls"),
+                (matched, ")"),
+            ])
+        );
+        assert_eq!(
+            highlighter.highlight("echo (ls)", 7),
+            styled(&[(Style::new(), "echo (ls)")])
+        );
+    }
+
+    #[test]
+    fn unmatched_brackets_are_flagged() {
+        let highlighter = BracketHighlighter::default();
+        let unmatched = Color::Red.bold();
+
+        assert_eq!(
+            highlighter.highlight("(a] {", 0),
+            styled(&[
+                (unmatched, "("),
+                (Style::new(), "a"),
+                (unmatched, "]"),
+                (Style::new(), " "),
+                (unmatched, "{"),
+            ])
+        );
+    }
+
+    #[test]
+    fn nested_brackets_and_strings() {
+        let highlighter = BracketHighlighter::default().with_match_style(Style::new().bold());
+        let bold = Style::new().bold();
+
+        // The cursor is on the inner opening bracket, the bracket in the string is skipped
+        assert_eq!(
+            highlighter.highlight(r#"[a {")" b}]"#, 3),
+            styled(&[
+                (Style::new(), "[a "),
+                (bold, "{"),
+                (Style::new(), r#"")" b"#),
+                (bold, "}"),
+                (Style::new(), "]"),
+            ])
+        );
+    }
+
+    #[test]
+    fn styles_of_wrapped_highlighter_are_kept() {
+        struct Keyword;
+        impl Highlighter for Keyword {
+            fn highlight(&self, line: &str, _cursor: usize) -> StyledText {
+                styled(&[
+                    (Color::Green.normal(), &line[..2]),
+                    (Style::new(), &line[2..]),
+                ])
+            }
+        }
+        let highlighter = BracketHighlighter::new(Box::new(Keyword));
+
+        assert_eq!(
+            highlighter.highlight("ls (", 4),
+            styled(&[
+                (Color::Green.normal(), "ls"),
+                (Style::new(), " "),
+                (Color::Red.bold(), "("),
+            ])
+        );
+    }
+}
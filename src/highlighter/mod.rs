@@ -1,8 +1,12 @@
+mod async_highlighter;
+mod bracket_highlighter;
 mod example;
 mod simple_match;
 
 use crate::StyledText;
 
+pub use async_highlighter::{AsyncHighlighter, HighlightRequests};
+pub use bracket_highlighter::BracketHighlighter;
 pub use example::ExampleHighlighter;
 pub use simple_match::SimpleMatchHighlighter;
 /// The syntax highlighting trait. Implementers of this trait will take in the current string and then
@@ -12,4 +16,11 @@ pub trait Highlighter: Send {
     ///
     /// Cursor position as byte offsets in the string
     fn highlight(&self, line: &str, cursor: usize) -> StyledText;
+
+    /// The last returned styling is a stand-in until a result computed elsewhere arrives
+    ///
+    /// While this is true the engine repaints periodically to pick up the result.
+    fn is_pending(&self) -> bool {
+        false
+    }
 }
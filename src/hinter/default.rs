@@ -1,5 +1,8 @@
-use crate::{Hinter, History};
+use crate::{Hinter, History, WordBoundary};
 use nu_ansi_term::{Color, Style};
+use std::borrow::Cow;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 /// A hinter that use the completions or the history to show a hint to the user
 ///
@@ -8,6 +11,8 @@ pub struct DefaultHinter {
     style: Style,
     current_hint: String,
     min_chars: usize,
+    max_length: Option<usize>,
+    word_boundary: WordBoundary,
 }
 
 impl Hinter for DefaultHinter {
@@ -28,10 +33,11 @@ impl Hinter for DefaultHinter {
             String::new()
         };
 
-        if use_ansi_coloring && !self.current_hint.is_empty() {
-            self.style.paint(&self.current_hint).to_string()
+        let displayed_hint = self.displayed_hint();
+        if use_ansi_coloring && !displayed_hint.is_empty() {
+            self.style.paint(displayed_hint.as_ref()).to_string()
         } else {
-            self.current_hint.clone()
+            displayed_hint.into_owned()
         }
     }
 
@@ -40,21 +46,10 @@ impl Hinter for DefaultHinter {
     }
 
     fn next_hint_token(&self) -> String {
-        let mut reached_content = false;
-        let result: String = self
-            .current_hint
-            .chars()
-            .take_while(|c| match (c.is_whitespace(), reached_content) {
-                (true, true) => false,
-                (true, false) => true,
-                (false, true) => true,
-                (false, false) => {
-                    reached_content = true;
-                    true
-                }
-            })
-            .collect();
-        result
+        match self.word_boundary.words(&self.current_hint).next() {
+            Some(word) => self.current_hint[..word.end].to_string(),
+            None => self.current_hint.clone(),
+        }
     }
 }
 
@@ -64,6 +59,8 @@ impl Default for DefaultHinter {
             style: Style::new().fg(Color::LightGray),
             current_hint: String::new(),
             min_chars: 1,
+            max_length: None,
+            word_boundary: WordBoundary::Whitespace,
         }
     }
 }
@@ -82,4 +79,121 @@ impl DefaultHinter {
         self.min_chars = min_chars;
         self
     }
+
+    /// A builder that limits the displayed hint to `max_length` terminal columns
+    ///
+    /// Longer hints are cut between graphemes and end with `…`. Accepting the hint still
+    /// inserts it completely.
+    #[must_use]
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    fn displayed_hint(&self) -> Cow<'_, str> {
+        match self.max_length {
+            Some(max_length) if self.current_hint.width() > max_length => {
+                // One column is left for the ellipsis
+                let mut width = 0;
+                let mut truncated: String = self
+                    .current_hint
+                    .graphemes(true)
+                    .take_while(|grapheme| {
+                        width += grapheme.width();
+                        width < max_length
+                    })
+                    .collect();
+                if max_length > 0 {
+                    truncated.push('…');
+                }
+                Cow::Owned(truncated)
+            }
+            _ => Cow::Borrowed(&self.current_hint),
+        }
+    }
+
+    /// A builder that sets which part of the hint is accepted by
+    /// [`crate::ReedlineEvent::HistoryHintWordComplete`] (whitespace separated words by default)
+    #[must_use]
+    pub fn with_word_boundary(mut self, word_boundary: WordBoundary) -> Self {
+        self.word_boundary = word_boundary;
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::FileBackedHistory;
+    use pretty_assertions::assert_eq;
+
+    fn history_with(entries: &[&str]) -> FileBackedHistory {
+        let mut history = FileBackedHistory::default();
+        for entry in entries {
+            history.append(entry);
+        }
+        history
+    }
+
+    #[test]
+    fn word_complete_consumes_hint_incrementally() {
+        let history = history_with(&["git commit --amend"]);
+        let mut hinter = DefaultHinter::default();
+        let mut line = String::from("git");
+
+        let mut accepted = Vec::new();
+        loop {
+            hinter.handle(&line, line.len(), &history, false);
+            let token = hinter.next_hint_token();
+            if token.is_empty() {
+                break;
+            }
+            line.push_str(&token);
+            accepted.push(token);
+        }
+
+        assert_eq!(accepted, vec![" commit", " --amend"]);
+        assert_eq!(line, "git commit --amend");
+    }
+
+    #[test]
+    fn long_hint_is_truncated_but_accepted_completely() {
+        let history = history_with(&["echo 'a\u{301}bcdef' | grep b"]);
+        let mut hinter = DefaultHinter::default().with_max_length(5);
+
+        let displayed = hinter.handle("echo", 4, &history, false);
+
+        // The combining accent stays with its letter
+        assert_eq!(displayed, " 'a\u{301}b…");
+        assert_eq!(hinter.complete_hint(), " 'a\u{301}bcdef' | grep b");
+        assert_eq!(hinter.next_hint_token(), " 'a\u{301}bcdef'");
+
+        hinter.handle("echo 'a\u{301}bcdef' | grep", 20, &history, false);
+        assert_eq!(hinter.complete_hint(), " b");
+    }
+
+    #[test]
+    fn wide_characters_are_truncated_by_width() {
+        let history = history_with(&["echo \u{65e5}\u{672c}\u{8a9e}"]);
+        let mut hinter = DefaultHinter::default().with_max_length(5);
+
+        let displayed = hinter.handle("echo", 4, &history, false);
+
+        assert_eq!(displayed, " \u{65e5}\u{2026}");
+        assert_eq!(displayed.width(), 4);
+        assert_eq!(hinter.complete_hint(), " \u{65e5}\u{672c}\u{8a9e}");
+    }
+
+    #[test]
+    fn word_complete_uses_word_boundary() {
+        let history = history_with(&["cd ~/projects/reedline"]);
+        let mut hinter =
+            DefaultHinter::default().with_word_boundary(WordBoundary::Separators(vec!['/']));
+
+        hinter.handle("cd ~", 4, &history, false);
+        assert_eq!(hinter.next_hint_token(), "/projects");
+
+        hinter.handle("cd ~/projects", 13, &history, false);
+        assert_eq!(hinter.next_hint_token(), "/reedline");
+    }
 }
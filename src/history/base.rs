@@ -1,5 +1,65 @@
 use crate::core_editor::LineBuffer;
-use std::collections::vec_deque::Iter;
+use std::{
+    collections::vec_deque::Iter,
+    fmt::{Display, Formatter},
+    io,
+};
+
+/// Errors of the fallible [`History`] operations
+#[derive(Debug)]
+pub enum HistoryError {
+    /// Reading or writing the history file failed
+    Io(io::Error),
+    /// An entry is longer than the configured maximum length in bytes
+    TooLong {
+        /// Length of the entry in bytes
+        len: usize,
+        /// Maximum length in bytes
+        max_len: usize,
+    },
+}
+
+impl Display for HistoryError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            HistoryError::Io(error) => write!(f, "history file error: {}", error),
+            HistoryError::TooLong { len, max_len } => write!(
+                f,
+                "history entry of {} bytes exceeds the maximum of {} bytes",
+                len, max_len
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HistoryError::Io(error) => Some(error),
+            HistoryError::TooLong { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for HistoryError {
+    fn from(error: io::Error) -> Self {
+        HistoryError::Io(error)
+    }
+}
+
+impl From<HistoryError> for io::Error {
+    fn from(error: HistoryError) -> Self {
+        match error {
+            HistoryError::Io(error) => error,
+            error @ HistoryError::TooLong { .. } => {
+                io::Error::new(io::ErrorKind::InvalidInput, error.to_string())
+            }
+        }
+    }
+}
+
+/// Result of the fallible [`History`] operations
+pub type Result<T> = std::result::Result<T, HistoryError>;
 
 /// Browsing modes for a [`History`]
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -14,6 +74,51 @@ pub enum HistoryNavigationQuery {
     // Fuzzy Search
 }
 
+/// Order of the entries returned by [`History::search`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchDirection {
+    /// Newest entries first
+    Backward,
+    /// Oldest entries first
+    Forward,
+}
+
+/// Query for the entries of a [`History`] with [`History::search`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    /// Text the entries contain, every entry contains an empty string
+    pub substring: String,
+    /// Order of the returned entries
+    pub direction: SearchDirection,
+    /// Maximum number of returned entries, all matches if `None`
+    pub limit: Option<usize>,
+}
+
+impl SearchQuery {
+    /// Query for all entries containing `substring`, newest first
+    pub fn substring(substring: impl Into<String>) -> Self {
+        Self {
+            substring: substring.into(),
+            direction: SearchDirection::Backward,
+            limit: None,
+        }
+    }
+
+    /// Query builder with the order of the returned entries
+    #[must_use]
+    pub fn with_direction(mut self, direction: SearchDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Query builder with the maximum number of returned entries
+    #[must_use]
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
 /// Interface of a history datastructure that supports stateful navigation via [`HistoryNavigationQuery`].
 pub trait History: Send {
     /// Append entry to the history, if capacity management is part of the implementation may perform that as well
@@ -44,11 +149,29 @@ pub trait History: Send {
     /// Query the values in the history entries
     fn query_entries(&self, search: &str) -> Vec<String>;
 
+    /// Entries matching the query in its direction, up to its limit
+    fn search(&self, query: &SearchQuery) -> Vec<String> {
+        let matches = self
+            .iter_chronologic()
+            .filter(|entry| entry.contains(&query.substring));
+        let limit = query.limit.unwrap_or(usize::MAX);
+
+        match query.direction {
+            SearchDirection::Backward => matches.rev().take(limit).cloned().collect(),
+            SearchDirection::Forward => matches.take(limit).cloned().collect(),
+        }
+    }
+
     /// Max number of values that can be queried from the history
     fn max_values(&self) -> usize;
 
     /// Synchronize the state of the history with the backing filesystem or database if available
-    fn sync(&mut self) -> std::io::Result<()>;
+    fn sync(&mut self) -> Result<()>;
+
+    /// Remove all entries, including the ones in the backing filesystem or database
+    ///
+    /// Works on an empty history as well.
+    fn clear(&mut self) -> Result<()>;
 
     /// Reset the browsing cursor back outside the history, does not affect the [`HistoryNavigationQuery`]
     fn reset_cursor(&mut self);
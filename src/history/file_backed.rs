@@ -1,9 +1,9 @@
-use super::{base::HistoryNavigationQuery, History};
+use super::{base::HistoryNavigationQuery, History, HistoryError, Result, SearchQuery};
 use crate::core_editor::LineBuffer;
 use std::{
-    collections::{vec_deque::Iter, VecDeque},
+    collections::{vec_deque::Iter, HashMap, HashSet, VecDeque},
     fs::OpenOptions,
-    io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write},
+    io::{self, BufRead, BufReader, BufWriter, Seek, SeekFrom, Write},
     ops::{Deref, DerefMut},
     path::PathBuf,
 };
@@ -12,6 +12,16 @@ use std::{
 pub const HISTORY_SIZE: usize = 1000;
 pub const NEWLINE_ESCAPE: &str = "<\\n>";
 
+/// What [`FileBackedHistory`] does with entries over the length set with
+/// [`FileBackedHistory::with_max_entry_len()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LongEntryPolicy {
+    /// The entry is not recorded
+    Reject,
+    /// The entry is cut at the last character boundary within the maximum length
+    Truncate,
+}
+
 /// Stateful history that allows up/down-arrow browsing with an internal cursor.
 ///
 /// Can optionally be associated with a newline separated history file using the [`FileBackedHistory::with_file()`] constructor.
@@ -25,6 +35,10 @@ pub struct FileBackedHistory {
     cursor: usize, // If cursor == entries.len() outside history browsing
     file: Option<PathBuf>,
     len_on_disk: usize, // Keep track what was previously written to disk
+    dedup_on_load: bool,
+    dedup_consecutive: bool,
+    skip_blank: bool,
+    max_entry_len: Option<(usize, LongEntryPolicy)>,
     query: HistoryNavigationQuery,
 }
 
@@ -45,27 +59,83 @@ fn decode_entry(s: &str) -> String {
     s.replace(NEWLINE_ESCAPE, "\n")
 }
 
+/// Reads the newest `capacity` entries of a history file and the number of lines in it
+///
+/// Only these entries are kept while reading, so large files are never loaded completely.
+/// With `dedup` only the newest occurrence of an entry is kept and the entries in `newer`
+/// are skipped.
+fn read_newest_entries<'a>(
+    reader: impl BufRead,
+    capacity: usize,
+    dedup: bool,
+    newer: impl Iterator<Item = &'a String>,
+) -> io::Result<(VecDeque<String>, usize)> {
+    let newer: HashSet<&str> = if dedup {
+        newer.map(String::as_str).collect()
+    } else {
+        HashSet::new()
+    };
+    // Line of the newest occurrence of every entry, other occurrences in `entries`
+    // are stale and skipped
+    let mut newest: HashMap<String, usize> = HashMap::new();
+    let is_kept = |newest: &HashMap<String, usize>, (index, entry): &(usize, String)| {
+        !dedup || newest.get(entry) == Some(index)
+    };
+    let mut entries: VecDeque<(usize, String)> = VecDeque::new();
+    let mut kept = 0;
+    let mut lines_in_file = 0;
+
+    for line in reader.lines() {
+        let entry = decode_entry(&line?);
+        let line_index = lines_in_file;
+        lines_in_file += 1;
+
+        if dedup {
+            if newer.contains(entry.as_str()) {
+                continue;
+            }
+            if newest.insert(entry.clone(), line_index).is_some() {
+                kept -= 1;
+            }
+        }
+        entries.push_back((line_index, entry));
+        kept += 1;
+
+        while kept > capacity {
+            match entries.pop_front() {
+                Some(oldest) if is_kept(&newest, &oldest) => {
+                    newest.remove(&oldest.1);
+                    kept -= 1;
+                }
+                Some(_stale) => {}
+                None => break,
+            }
+        }
+        // Drop the stale occurrences before they outgrow the kept entries
+        if entries.len() > 2 * kept.max(capacity) {
+            entries.retain(|entry| is_kept(&newest, entry));
+        }
+    }
+
+    let entries = entries
+        .into_iter()
+        .filter(|entry| is_kept(&newest, entry))
+        .map(|(_, entry)| entry)
+        .collect();
+
+    Ok((entries, lines_in_file))
+}
+
 impl History for FileBackedHistory {
     /// Appends an entry if non-empty and not repetition of the previous entry.
     /// Resets the browsing cursor to the default state in front of the most recent entry.
     ///
+    /// Repetitions are kept with [`FileBackedHistory::with_dedup_consecutive()`] set to `false`,
+    /// whitespace-only entries are skipped with [`FileBackedHistory::with_skip_blank()`]
+    /// and long ones are handled as set with [`FileBackedHistory::with_max_entry_len()`].
+    /// Use [`FileBackedHistory::try_append()`] to know if a long entry was rejected.
     fn append(&mut self, entry: &str) {
-        // Don't append if the preceding value is identical or the string empty
-        if self
-            .entries
-            .back()
-            .map_or(true, |previous| previous != entry)
-            && !entry.is_empty()
-        {
-            if self.entries.len() == self.capacity {
-                // History is "full", so we delete the oldest entry first,
-                // before adding a new one.
-                self.entries.pop_front();
-                self.len_on_disk = self.len_on_disk.saturating_sub(1);
-            }
-            self.entries.push_back(entry.to_string());
-        }
-        self.reset_cursor();
+        let _rejected = self.try_append(entry);
     }
 
     fn iter_chronologic(&self) -> Iter<'_, String> {
@@ -118,11 +188,7 @@ impl History for FileBackedHistory {
     }
 
     fn query_entries(&self, search: &str) -> Vec<String> {
-        self.iter_chronologic()
-            .rev()
-            .filter(|entry| entry.contains(search))
-            .cloned()
-            .collect::<Vec<String>>()
+        self.search(&SearchQuery::substring(search))
     }
 
     fn max_values(&self) -> usize {
@@ -132,10 +198,14 @@ impl History for FileBackedHistory {
     /// Writes unwritten history contents to disk.
     ///
     /// If file would exceed `capacity` truncates the oldest entries.
-    fn sync(&mut self) -> std::io::Result<()> {
+    /// With [`FileBackedHistory::with_dedup_on_load()`] older duplicates are removed
+    /// from the file as well. This is checked on every sync, so appending an entry
+    /// that is already in the file rewrites the whole file.
+    fn sync(&mut self) -> Result<()> {
         if let Some(fname) = &self.file {
             // The unwritten entries
             let own_entries = self.entries.range(self.len_on_disk..);
+            let foreign_capacity = self.capacity - own_entries.len();
 
             let mut f_lock = fd_lock::RwLock::new(
                 OpenOptions::new()
@@ -147,18 +217,15 @@ impl History for FileBackedHistory {
             let mut writer_guard = f_lock.write()?;
             let (mut foreign_entries, truncate) = {
                 let reader = BufReader::new(writer_guard.deref());
-                let mut from_file = reader
-                    .lines()
-                    .map(|o| o.map(|i| decode_entry(&i)))
-                    .collect::<Result<VecDeque<_>, _>>()?;
-                if from_file.len() + own_entries.len() > self.capacity {
-                    (
-                        from_file.split_off(from_file.len() - (self.capacity - own_entries.len())),
-                        true,
-                    )
-                } else {
-                    (from_file, false)
-                }
+                let (from_file, lines_in_file) = read_newest_entries(
+                    reader,
+                    foreign_capacity,
+                    self.dedup_on_load,
+                    own_entries.clone(),
+                )?;
+
+                let truncate = from_file.len() != lines_in_file;
+                (from_file, truncate)
             };
 
             {
@@ -197,6 +264,28 @@ impl History for FileBackedHistory {
         Ok(())
     }
 
+    /// Removes all entries and truncates the history file
+    fn clear(&mut self) -> Result<()> {
+        if let Some(fname) = &self.file {
+            // Truncated once the lock is held
+            let mut f_lock = fd_lock::RwLock::new(
+                OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .truncate(false)
+                    .open(fname)?,
+            );
+            let writer_guard = f_lock.write()?;
+            writer_guard.set_len(0)?;
+        }
+
+        self.entries.clear();
+        self.len_on_disk = 0;
+        self.reset_cursor();
+
+        Ok(())
+    }
+
     /// Reset the internal browsing cursor
     fn reset_cursor(&mut self) {
         self.cursor = self.entries.len();
@@ -219,6 +308,10 @@ impl FileBackedHistory {
             cursor: 0,
             file: None,
             len_on_disk: 0,
+            dedup_on_load: false,
+            dedup_consecutive: true,
+            skip_blank: false,
+            max_entry_len: None,
             query: HistoryNavigationQuery::Normal(LineBuffer::default()),
         }
     }
@@ -231,14 +324,117 @@ impl FileBackedHistory {
     ///
     /// **Side effects:** creates all nested directories to the file
     ///
-    pub fn with_file(capacity: usize, file: PathBuf) -> std::io::Result<Self> {
-        let mut hist = Self::new(capacity);
+    pub fn with_file(capacity: usize, file: PathBuf) -> Result<Self> {
+        Self::new(capacity).with_history_file(file)
+    }
+
+    /// Associates the history with a file, like [`FileBackedHistory::with_file()`]
+    ///
+    /// Allows to configure the history before the file is read.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use reedline::FileBackedHistory;
+    ///
+    /// let history = FileBackedHistory::new(1000)
+    ///     .with_dedup_on_load(true)
+    ///     .with_history_file("history.txt".into())
+    ///     .expect("Error configuring history with file");
+    /// ```
+    ///
+    /// **Side effects:** creates all nested directories to the file
+    pub fn with_history_file(mut self, file: PathBuf) -> Result<Self> {
         if let Some(base_dir) = file.parent() {
             std::fs::create_dir_all(base_dir)?;
         }
-        hist.file = Some(file);
-        hist.sync()?;
-        Ok(hist)
+        self.file = Some(file);
+        self.sync()?;
+        Ok(self)
+    }
+
+    /// Builder to drop older duplicates of an entry when reading the history file
+    ///
+    /// Only the newest occurrence of an entry is kept, in memory and in the file.
+    /// Together with the capacity this keeps the newest distinct entries of large files.
+    #[must_use]
+    pub fn with_dedup_on_load(mut self, dedup_on_load: bool) -> Self {
+        self.dedup_on_load = dedup_on_load;
+        self
+    }
+
+    /// Builder to skip an entry that repeats the previous one (enabled by default)
+    ///
+    /// Only the directly preceding entry is compared, so a command run again after
+    /// others is still recorded.
+    #[must_use]
+    pub fn with_dedup_consecutive(mut self, dedup_consecutive: bool) -> Self {
+        self.dedup_consecutive = dedup_consecutive;
+        self
+    }
+
+    /// Builder to skip entries with only whitespace, not just empty ones (disabled by default)
+    #[must_use]
+    pub fn with_skip_blank(mut self, skip_blank: bool) -> Self {
+        self.skip_blank = skip_blank;
+        self
+    }
+
+    /// Builder to limit entries to `max_len` bytes, e.g. against a runaway paste
+    ///
+    /// Longer entries are either rejected or truncated, depending on `policy`. Keep in mind
+    /// that a truncated command could do something else when run again.
+    #[must_use]
+    pub fn with_max_entry_len(mut self, max_len: usize, policy: LongEntryPolicy) -> Self {
+        self.max_entry_len = Some((max_len, policy));
+        self
+    }
+
+    /// Appends an entry like [`History::append()`]
+    ///
+    /// # Errors
+    ///
+    /// [`HistoryError::TooLong`] if the entry is longer than the maximum length set with
+    /// [`FileBackedHistory::with_max_entry_len()`] and [`LongEntryPolicy::Reject`].
+    /// The entry is not recorded then.
+    pub fn try_append(&mut self, entry: &str) -> Result<()> {
+        let entry = match self.max_entry_len {
+            Some((max_len, policy)) if entry.len() > max_len => match policy {
+                LongEntryPolicy::Reject => {
+                    self.reset_cursor();
+                    return Err(HistoryError::TooLong {
+                        len: entry.len(),
+                        max_len,
+                    });
+                }
+                LongEntryPolicy::Truncate => {
+                    let end = (0..=max_len)
+                        .rev()
+                        .find(|&index| entry.is_char_boundary(index))
+                        .unwrap_or_default();
+                    &entry[..end]
+                }
+            },
+            _ => entry,
+        };
+        // Don't append if the preceding value is identical or the string empty
+        let repeated =
+            self.dedup_consecutive && self.entries.back().map(String::as_str) == Some(entry);
+        let empty = if self.skip_blank {
+            entry.trim().is_empty()
+        } else {
+            entry.is_empty()
+        };
+        if !repeated && !empty && self.capacity > 0 {
+            if self.entries.len() == self.capacity {
+                // History is "full", so we delete the oldest entry first,
+                // before adding a new one.
+                self.entries.pop_front();
+                self.len_on_disk = self.len_on_disk.saturating_sub(1);
+            }
+            self.entries.push_back(entry.to_string());
+        }
+        self.reset_cursor();
+        Ok(())
     }
 
     fn back_with_criteria(&mut self, criteria: &dyn Fn(&str) -> bool) {
@@ -285,6 +481,7 @@ impl Drop for FileBackedHistory {
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
+    use rstest::rstest;
 
     use super::*;
 
@@ -343,6 +540,35 @@ mod tests {
         hist.append("unique");
         assert_eq!(hist.entries.len(), 3);
     }
+
+    #[test]
+    fn consecutive_duplicates_can_be_kept() {
+        let entries = ["ls", "ls", "cd", "ls"];
+
+        let mut hist = FileBackedHistory::default();
+        entries.iter().for_each(|entry| hist.append(entry));
+        assert_eq!(hist.entries, ["ls", "cd", "ls"]);
+
+        let mut hist = FileBackedHistory::default().with_dedup_consecutive(false);
+        entries.iter().for_each(|entry| hist.append(entry));
+        assert_eq!(hist.entries, entries);
+    }
+
+    #[test]
+    fn consecutive_duplicate_of_entry_from_file_is_skipped() {
+        use tempfile::tempdir;
+
+        let tmp = tempdir().unwrap();
+        let histfile = tmp.path().join(".history");
+        std::fs::write(&histfile, "cd\nls\n").unwrap();
+
+        let mut hist = FileBackedHistory::with_file(5, histfile.clone()).unwrap();
+        hist.append("ls");
+        hist.sync().unwrap();
+
+        assert_eq!(std::fs::read_to_string(&histfile).unwrap(), "cd\nls\n");
+    }
+
     #[test]
     fn appends_no_empties() {
         let mut hist = FileBackedHistory::default();
@@ -350,6 +576,51 @@ mod tests {
         assert_eq!(hist.entries.len(), 0);
     }
 
+    #[test]
+    fn skip_blank_skips_whitespace_only_entries() {
+        let entries = ["", "   ", "\t\n", "ls -la"];
+
+        let mut hist = FileBackedHistory::default();
+        entries.iter().for_each(|entry| hist.append(entry));
+        assert_eq!(hist.entries, ["   ", "\t\n", "ls -la"]);
+
+        let mut hist = FileBackedHistory::default().with_skip_blank(true);
+        entries.iter().for_each(|entry| hist.append(entry));
+        assert_eq!(hist.entries, ["ls -la"]);
+    }
+
+    #[rstest]
+    #[case(LongEntryPolicy::Reject, &["echo ä", "ls"])]
+    #[case(LongEntryPolicy::Truncate, &["echo ä", "echo a", "xxxxxxx", "ls"])]
+    fn max_entry_len_limits_long_entries(
+        #[case] policy: LongEntryPolicy,
+        #[case] expected: &[&str],
+    ) {
+        let mut hist = FileBackedHistory::default().with_max_entry_len(7, policy);
+        // 7 bytes, at the limit
+        hist.append("echo ä");
+        // 8 bytes, just over the limit, the limit falls within the two bytes of 'ä'
+        hist.append("echo aä");
+        hist.append(&"x".repeat(10_000));
+        hist.append("ls");
+
+        assert_eq!(hist.entries, expected);
+    }
+
+    #[test]
+    fn rejected_entry_is_reported() {
+        let mut hist = FileBackedHistory::default().with_max_entry_len(7, LongEntryPolicy::Reject);
+
+        assert!(hist.try_append("echo ä").is_ok());
+        let error = hist.try_append("echo äa").unwrap_err();
+
+        assert!(matches!(
+            error,
+            HistoryError::TooLong { len: 8, max_len: 7 }
+        ));
+        assert_eq!(hist.entries, ["echo ä"]);
+    }
+
     #[test]
     fn prefix_search_works() {
         let mut hist = FileBackedHistory::default();
@@ -472,6 +743,35 @@ mod tests {
         assert_eq!(hist.string_at_cursor(), None);
     }
 
+    #[test]
+    fn search_honors_direction_and_limit() {
+        use crate::SearchDirection;
+
+        let mut hist = FileBackedHistory::default();
+        ["git add", "ls", "git commit", "git push"]
+            .iter()
+            .for_each(|entry| hist.append(entry));
+
+        let query = SearchQuery::substring("git");
+        assert_eq!(
+            hist.search(&query),
+            vec!["git push", "git commit", "git add"]
+        );
+        assert_eq!(hist.query_entries("git"), hist.search(&query));
+        assert_eq!(
+            hist.search(&query.clone().with_limit(2)),
+            vec!["git push", "git commit"]
+        );
+
+        let query = query.with_direction(SearchDirection::Forward);
+        assert_eq!(
+            hist.search(&query.clone().with_limit(2)),
+            vec!["git add", "git commit"]
+        );
+        assert_eq!(hist.search(&query.with_limit(0)), Vec::<String>::new());
+        assert_eq!(hist.search(&SearchQuery::substring("")).len(), 4);
+    }
+
     #[test]
     fn writes_to_new_file() {
         use tempfile::tempdir;
@@ -498,6 +798,43 @@ mod tests {
         tmp.close().unwrap();
     }
 
+    #[test]
+    fn file_errors_are_typed() {
+        use crate::HistoryError;
+        use std::error::Error;
+        use tempfile::tempdir;
+
+        // A directory can't be opened as the history file
+        let tmp = tempdir().unwrap();
+        let error = FileBackedHistory::with_file(5, tmp.path().to_path_buf()).unwrap_err();
+
+        assert!(matches!(error, HistoryError::Io(_)));
+        assert!(error.source().is_some());
+        assert!(error.to_string().starts_with("history file error: "));
+    }
+
+    #[test]
+    fn clear_removes_entries_in_memory_and_file() {
+        use tempfile::tempdir;
+
+        let tmp = tempdir().unwrap();
+        let histfile = tmp.path().join(".history");
+        std::fs::write(&histfile, "ls\ncd\n").unwrap();
+
+        let mut hist = FileBackedHistory::with_file(5, histfile.clone()).unwrap();
+        hist.append("pwd");
+        hist.clear().unwrap();
+        assert_eq!(hist.iter_chronologic().count(), 0);
+        assert_eq!(hist.string_at_cursor(), None);
+        assert_eq!(std::fs::read_to_string(&histfile).unwrap(), "");
+
+        // Clearing again is fine and new entries are written as usual
+        hist.clear().unwrap();
+        hist.append("echo");
+        hist.sync().unwrap();
+        assert_eq!(std::fs::read_to_string(&histfile).unwrap(), "echo\n");
+    }
+
     #[test]
     fn persists_newlines_in_entries() {
         use tempfile::tempdir;
@@ -528,6 +865,44 @@ mod tests {
         tmp.close().unwrap();
     }
 
+    #[test]
+    fn keeps_newest_entries_up_to_capacity() {
+        use tempfile::tempdir;
+
+        let tmp = tempdir().unwrap();
+        let histfile = tmp.path().join(".history");
+        let capacity = 20;
+        let entries: Vec<String> = (0..capacity + 10).map(|i| format!("cmd {}", i)).collect();
+
+        let mut hist = FileBackedHistory::with_file(capacity, histfile.clone()).unwrap();
+        entries.iter().for_each(|entry| hist.append(entry));
+        assert_eq!(hist.iter_chronologic().count(), capacity);
+        assert_eq!(hist.iter_chronologic().next(), Some(&entries[10]));
+
+        hist.sync().unwrap();
+        let file_entries: Vec<String> = std::fs::read_to_string(&histfile)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(file_entries, entries[10..]);
+    }
+
+    #[test]
+    fn zero_capacity_keeps_nothing() {
+        use tempfile::tempdir;
+
+        let tmp = tempdir().unwrap();
+        let histfile = tmp.path().join(".history");
+
+        let mut hist = FileBackedHistory::with_file(0, histfile.clone()).unwrap();
+        hist.append("ls");
+        hist.sync().unwrap();
+
+        assert_eq!(hist.iter_chronologic().count(), 0);
+        assert_eq!(std::fs::read_to_string(&histfile).unwrap(), "");
+    }
+
     #[test]
     fn truncates_file_to_capacity() {
         use tempfile::tempdir;
@@ -623,6 +998,64 @@ mod tests {
         tmp.close().unwrap();
     }
 
+    #[test]
+    fn dedups_and_caps_oversized_file() {
+        use tempfile::tempdir;
+
+        let tmp = tempdir().unwrap();
+        let histfile = tmp.path().join(".history");
+
+        let legacy_entries: String = (0..1000)
+            .map(|i| format!("cmd {}\n", i % 10))
+            .chain(["ls\n".to_string(), "cmd 3\n".to_string()])
+            .collect();
+        std::fs::write(&histfile, legacy_entries).unwrap();
+        let expected_entries = vec!["cmd 7", "cmd 8", "cmd 9", "ls", "cmd 3"];
+
+        {
+            let hist = FileBackedHistory::new(5)
+                .with_dedup_on_load(true)
+                .with_history_file(histfile.clone())
+                .unwrap();
+
+            let actual: Vec<_> = hist.iter_chronologic().collect();
+            assert_eq!(expected_entries, actual);
+        }
+
+        // The file is truncated to the same entries
+        let file_entries: Vec<String> = std::fs::read_to_string(&histfile)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(expected_entries, file_entries);
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn dedup_on_load_keeps_newest_own_entry() {
+        use tempfile::tempdir;
+
+        let tmp = tempdir().unwrap();
+        let histfile = tmp.path().join(".history");
+        std::fs::write(&histfile, "ls\ncd\npwd\n").unwrap();
+
+        {
+            let mut hist = FileBackedHistory::new(10)
+                .with_dedup_on_load(true)
+                .with_history_file(histfile.clone())
+                .unwrap();
+            hist.append("ls");
+        }
+
+        let reading_hist = FileBackedHistory::with_file(10, histfile).unwrap();
+        let actual: Vec<_> = reading_hist.iter_chronologic().collect();
+        assert_eq!(vec!["cd", "pwd", "ls"], actual);
+
+        tmp.close().unwrap();
+    }
+
     #[test]
     fn concurrent_histories_dont_erase_eachother() {
         use tempfile::tempdir;
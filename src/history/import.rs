@@ -0,0 +1,139 @@
+use super::{FileBackedHistory, History, Result};
+use std::{fs, path::Path};
+
+impl FileBackedHistory {
+    /// Appends the commands of a bash history file, e.g. `~/.bash_history`
+    ///
+    /// Timestamp lines written with `HISTTIMEFORMAT` are skipped and a line ending with
+    /// a backslash continues on the next line. Returns the number of commands read,
+    /// which are appended like typed entries, so capacity and dedup settings apply.
+    pub fn import_bash(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        let content = fs::read(path)?;
+        Ok(self.append_imported(parse_bash_history(&String::from_utf8_lossy(&content))))
+    }
+
+    /// Appends the commands of a zsh history file, e.g. `~/.zsh_history`
+    ///
+    /// Reads plain lines and the extended format `: <start>:<elapsed>;<command>`, whose
+    /// timestamps are dropped. Malformed extended lines are skipped. Returns the number of
+    /// commands read, which are appended like typed entries.
+    pub fn import_zsh(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        let content = fs::read(path)?;
+        Ok(self.append_imported(parse_zsh_history(&String::from_utf8_lossy(&content))))
+    }
+
+    fn append_imported(&mut self, commands: Vec<String>) -> usize {
+        for command in &commands {
+            self.append(command);
+        }
+        commands.len()
+    }
+}
+
+/// Lines joined with the next one when they end with a backslash
+fn continued_lines(content: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current: Option<String> = None;
+
+    for line in content.lines() {
+        let line = match current.take() {
+            Some(previous) => previous + "\n" + line,
+            None => line.to_string(),
+        };
+        if line.ends_with('\\') {
+            current = Some(line);
+        } else {
+            lines.push(line);
+        }
+    }
+    lines.extend(current);
+
+    lines
+}
+
+fn parse_bash_history(content: &str) -> Vec<String> {
+    continued_lines(content)
+        .into_iter()
+        .filter(|line| !line.trim().is_empty() && !is_bash_timestamp(line))
+        .collect()
+}
+
+fn is_bash_timestamp(line: &str) -> bool {
+    matches!(line.strip_prefix('#'), Some(seconds) if is_number(seconds))
+}
+
+fn is_number(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn parse_zsh_history(content: &str) -> Vec<String> {
+    continued_lines(content)
+        .into_iter()
+        .filter_map(|line| {
+            // zsh escapes the newlines of a command with a backslash
+            let line = line.replace("\\\n", "\n");
+            let command = match line.strip_prefix(": ") {
+                Some(extended) => {
+                    let (header, command) = extended.split_once(';')?;
+                    let (start, elapsed) = header.split_once(':')?;
+                    if !is_number(start) || !is_number(elapsed) {
+                        return None;
+                    }
+                    command.to_string()
+                }
+                None => line,
+            };
+            (!command.trim().is_empty()).then_some(command)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_bash_history() {
+        let content = "ls -la\n#1700000000\ngit status\necho a \\\nb\n\ncd /tmp\n";
+
+        assert_eq!(
+            parse_bash_history(content),
+            vec!["ls -la", "git status", "echo a \\\nb", "cd /tmp"]
+        );
+    }
+
+    #[test]
+    fn parses_zsh_extended_history() {
+        let content = ": 1700000000:0;ls\n\
+                       : 1700000001:2;echo a\\\nb\n\
+                       : garbage;rm -rf /\n\
+                       : 1700000003:x;rm -rf /\n\
+                       : 1700000004:0;git log\n\
+                       plain\n";
+
+        assert_eq!(
+            parse_zsh_history(content),
+            vec!["ls", "echo a\nb", "git log", "plain"]
+        );
+    }
+
+    #[test]
+    fn imports_into_history() {
+        use tempfile::tempdir;
+
+        let tmp = tempdir().unwrap();
+        let bash_history = tmp.path().join(".bash_history");
+        let zsh_history = tmp.path().join(".zsh_history");
+        std::fs::write(&bash_history, "ls\nls\npwd\n").unwrap();
+        std::fs::write(&zsh_history, ": 1700000000:0;cd /\n: broken\n").unwrap();
+
+        let mut hist = FileBackedHistory::new(10);
+        assert_eq!(hist.import_bash(&bash_history).unwrap(), 3);
+        assert_eq!(hist.import_zsh(&zsh_history).unwrap(), 1);
+
+        let entries: Vec<_> = hist.iter_chronologic().collect();
+        assert_eq!(entries, vec!["ls", "pwd", "cd /"]);
+        assert!(hist.import_bash(tmp.path().join("missing")).is_err());
+    }
+}
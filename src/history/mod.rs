@@ -1,5 +1,8 @@
 mod base;
 mod file_backed;
+mod import;
 
-pub use base::{History, HistoryNavigationQuery};
-pub use file_backed::{FileBackedHistory, HISTORY_SIZE};
+pub use base::{
+    History, HistoryError, HistoryNavigationQuery, Result, SearchDirection, SearchQuery,
+};
+pub use file_backed::{FileBackedHistory, LongEntryPolicy, HISTORY_SIZE};
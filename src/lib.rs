@@ -28,9 +28,6 @@
 //!              println!("\nAborted!");
 //!              break;
 //!          }
-//!          Ok(Signal::CtrlL) => {
-//!              line_editor.clear_screen();
-//!          }
 //!          x => {
 //!              println!("Event: {:?}", x);
 //!          }
@@ -179,7 +176,7 @@
 #![warn(missing_docs)]
 // #![deny(warnings)]
 mod core_editor;
-pub use core_editor::LineBuffer;
+pub use core_editor::{ExternalEditor, LineBuffer, WordBoundary};
 
 mod enums;
 pub use enums::{EditCommand, ReedlineEvent, Signal, UndoBehavior};
@@ -191,7 +188,10 @@ mod engine;
 pub use engine::Reedline;
 
 mod history;
-pub use history::{FileBackedHistory, History, HistoryNavigationQuery, HISTORY_SIZE};
+pub use history::{
+    FileBackedHistory, History, HistoryError, HistoryNavigationQuery, LongEntryPolicy,
+    SearchDirection, SearchQuery, HISTORY_SIZE,
+};
 
 mod prompt;
 pub use prompt::{
@@ -202,14 +202,17 @@ pub use prompt::{
 mod edit_mode;
 pub use edit_mode::{
     default_emacs_keybindings, default_vi_insert_keybindings, default_vi_normal_keybindings,
-    EditMode, Emacs, Keybindings, Vi,
+    CursorConfig, EditMode, Emacs, KeyCombination, KeybindingConflict, Keybindings, Vi,
 };
 
 mod highlighter;
-pub use highlighter::{ExampleHighlighter, Highlighter, SimpleMatchHighlighter};
+pub use highlighter::{
+    AsyncHighlighter, BracketHighlighter, ExampleHighlighter, HighlightRequests, Highlighter,
+    SimpleMatchHighlighter,
+};
 
 mod completion;
-pub use completion::{Completer, DefaultCompleter, Span, Suggestion};
+pub use completion::{Completer, DefaultCompleter, FuzzyCompleter, FuzzyMatch, Span, Suggestion};
 
 mod hinter;
 pub use hinter::{DefaultHinter, Hinter};
@@ -218,7 +221,10 @@ mod validator;
 pub use validator::{DefaultValidator, ValidationResult, Validator};
 
 mod menu;
-pub use menu::{menu_functions, CompletionMenu, HistoryMenu, Menu, MenuEvent, MenuTextStyle};
+pub use menu::{
+    menu_functions, CompletionMenu, HistoryMenu, HistorySearchMode, HistorySearchStyle, Menu,
+    MenuEvent, MenuTextStyle,
+};
 
 mod utils;
 pub use utils::{
@@ -128,9 +128,8 @@ fn main() -> Result<()> {
             Ok(Signal::CtrlC) => {
                 // Prompt has been cleared and should start on the next line
             }
-            Ok(Signal::CtrlL) => {
-                line_editor.clear_screen()?;
-            }
+            // The deprecated `Signal::CtrlL` is not returned, Ctrl-L is handled by the engine
+            Ok(_) => {}
             Err(err) => {
                 println!("Error: {:?}", err);
             }
@@ -1,6 +1,14 @@
-use super::{menu_functions::find_common_string, Menu, MenuEvent, MenuTextStyle};
-use crate::{painting::Painter, Completer, History, LineBuffer, Suggestion};
-use nu_ansi_term::{ansi::RESET, Style};
+use super::{
+    menu_functions::{find_common_string, replace_in_buffer},
+    Menu, MenuEvent, MenuTextStyle,
+};
+use crate::{
+    painting::{line_width, Painter},
+    Completer, FuzzyMatch, History, LineBuffer, Suggestion,
+};
+use nu_ansi_term::{ansi::RESET, Color, Style};
+use std::{borrow::Cow, ops::Range};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 /// Default values used as reference for the menu. These values are set during
 /// the initial declaration of the menu and are always kept as reference for the
@@ -59,6 +67,37 @@ pub struct CompletionMenu {
     event: Option<MenuEvent>,
     /// Longest suggestion found in the values
     longest_suggestion: usize,
+    /// Maximum number of rows displayed at once
+    max_rows: Option<u16>,
+    /// Rows moved by a page up/down. Updated with the visible rows of the menu
+    page_rows: u16,
+    /// Show a scrollbar column when not all rows fit
+    scrollbar: bool,
+    /// Maximum number of lines a long description is wrapped onto
+    description_rows: u16,
+    /// Several values can be selected with [`MenuEvent::ToggleSelection`]
+    multi_select: bool,
+    /// Separator used to join the selected values in the buffer
+    selection_separator: String,
+    /// Indexes of the selected values in the order they were selected
+    selected: Vec<usize>,
+    /// Narrow the values of the last completer call while typing
+    incremental_filtering: bool,
+    /// Narrow the values by fuzzy matching instead of by prefix
+    fuzzy_filtering: bool,
+    /// Buffer, cursor and values of the last completer call
+    cache: Option<CompletionCache>,
+    /// Byte positions of the typed characters in each value
+    match_positions: Vec<Vec<usize>>,
+    /// Style of the typed characters in the values
+    match_style: Style,
+}
+
+/// Values returned by the completer for a buffer and cursor position
+struct CompletionCache {
+    buffer: String,
+    position: usize,
+    values: Vec<Suggestion>,
 }
 
 impl Default for CompletionMenu {
@@ -75,6 +114,18 @@ impl Default for CompletionMenu {
             marker: "| ".to_string(),
             event: None,
             longest_suggestion: 0,
+            max_rows: None,
+            page_rows: 1,
+            scrollbar: false,
+            description_rows: 1,
+            multi_select: false,
+            selection_separator: " ".to_string(),
+            selected: Vec::new(),
+            incremental_filtering: false,
+            fuzzy_filtering: false,
+            cache: None,
+            match_positions: Vec::new(),
+            match_style: Color::Yellow.bold(),
         }
     }
 }
@@ -129,6 +180,209 @@ impl CompletionMenu {
         self
     }
 
+    /// Menu builder with the maximum number of rows displayed at once
+    #[must_use]
+    pub fn with_max_rows(mut self, max_rows: u16) -> Self {
+        self.max_rows = Some(max_rows.max(1));
+        self
+    }
+
+    /// Menu builder to show a scrollbar when not all rows fit
+    #[must_use]
+    pub fn with_scrollbar(mut self, scrollbar: bool) -> Self {
+        self.scrollbar = scrollbar;
+        self
+    }
+
+    /// Menu builder to wrap long descriptions onto up to `description_rows` lines
+    ///
+    /// By default descriptions are shown in one line. A description that still doesn't fit
+    /// is shortened with an ellipsis. Every line of a description counts as a row of the
+    /// menu, for [`CompletionMenu::with_max_rows()`] and paging.
+    #[must_use]
+    pub fn with_description_rows(mut self, description_rows: u16) -> Self {
+        self.description_rows = description_rows.max(1);
+        self
+    }
+
+    /// Menu builder to select several values (single selection by default)
+    ///
+    /// [`crate::ReedlineEvent::MenuToggleSelection`] selects or unselects the value
+    /// under the cursor, and all selected values are inserted joined by the separator.
+    /// The default emacs and vi insert keybindings toggle the selection with Space,
+    /// which types a space as usual while no menu in multi select mode is open.
+    #[must_use]
+    pub fn with_multi_select(mut self, multi_select: bool) -> Self {
+        self.multi_select = multi_select;
+        self
+    }
+
+    /// Menu builder with the separator placed between the selected values
+    #[must_use]
+    pub fn with_selection_separator(mut self, selection_separator: String) -> Self {
+        self.selection_separator = selection_separator;
+        self
+    }
+
+    /// Menu builder to narrow the open menu while typing (disabled by default)
+    ///
+    /// The values of the last completer call are filtered with the typed text, which is
+    /// highlighted in them. A value is kept if it starts with the text from the start of
+    /// its span to the cursor, like [`crate::DefaultCompleter`] matches, so the result is
+    /// the same as calling the completer again. The completer is only called again when the
+    /// cursor moves before the position of that call, the text after the cursor changes,
+    /// whitespace is typed or none of the values match anymore. Deleting typed text widens
+    /// the values again.
+    #[must_use]
+    pub fn with_incremental_filtering(mut self, incremental_filtering: bool) -> Self {
+        self.incremental_filtering = incremental_filtering;
+        self
+    }
+
+    /// Menu builder to narrow the values by fuzzy matching instead of by prefix
+    ///
+    /// Only used with [`CompletionMenu::with_incremental_filtering()`]. Meant for a completer
+    /// that matches fuzzily as well, e.g. [`crate::FuzzyCompleter`], otherwise the menu
+    /// keeps values that calling the completer again wouldn't return.
+    #[must_use]
+    pub fn with_fuzzy_filtering(mut self, fuzzy_filtering: bool) -> Self {
+        self.fuzzy_filtering = fuzzy_filtering;
+        self
+    }
+
+    /// Menu builder with the style of the typed characters in the values
+    #[must_use]
+    pub fn with_match_style(mut self, match_style: Style) -> Self {
+        self.match_style = match_style;
+        self
+    }
+
+    /// Filters the values of the last completer call with the text typed since then
+    ///
+    /// Returns `None` if the cached values don't cover the buffer anymore.
+    fn narrow_cached_values(
+        &self,
+        buffer: &str,
+        position: usize,
+    ) -> Option<(Vec<Suggestion>, Vec<Vec<usize>>)> {
+        let cache = self.cache.as_ref()?;
+        if position < cache.position
+            || !buffer.is_char_boundary(position)
+            || !buffer.starts_with(&cache.buffer[..cache.position])
+            || buffer[position..] != cache.buffer[cache.position..]
+        {
+            return None;
+        }
+
+        let typed = &buffer[cache.position..position];
+        if typed.chars().any(char::is_whitespace) {
+            return None;
+        }
+
+        let narrowed: (Vec<Suggestion>, Vec<Vec<usize>>) = cache
+            .values
+            .iter()
+            .filter_map(|suggestion| {
+                let positions = self.typed_positions(buffer, position, suggestion);
+                if positions.is_none() && !typed.is_empty() {
+                    return None;
+                }
+
+                // Spans ending at the cursor of the completer call grow with the typed text
+                let mut suggestion = suggestion.clone();
+                if suggestion.span.end == cache.position {
+                    suggestion.span.end = position;
+                }
+                Some((suggestion, positions.unwrap_or_default()))
+            })
+            .unzip();
+
+        (!narrowed.0.is_empty()).then_some(narrowed)
+    }
+
+    /// Byte positions in the value of the text from the start of its span to the cursor
+    ///
+    /// Returns `None` if the value doesn't match the text.
+    fn typed_positions(
+        &self,
+        buffer: &str,
+        position: usize,
+        suggestion: &Suggestion,
+    ) -> Option<Vec<usize>> {
+        let typed = buffer.get(suggestion.span.start.min(position)..position)?;
+
+        if self.fuzzy_filtering {
+            FuzzyMatch::find(typed, &suggestion.value).map(|fuzzy_match| fuzzy_match.positions)
+        } else {
+            suggestion
+                .value
+                .starts_with(typed)
+                .then(|| typed.char_indices().map(|(index, _)| index).collect())
+        }
+    }
+
+    /// Value with the typed characters highlighted
+    fn highlight_matches(&self, index: usize, value: &str, text_style: Style) -> String {
+        let positions = match self.match_positions.get(index) {
+            Some(positions) if !positions.is_empty() => positions,
+            _ => return value.to_string(),
+        };
+        // The displayed value may start with the selection mark
+        let offset = value.len() - self.values[index].value.len();
+
+        value
+            .char_indices()
+            .map(|(position, c)| {
+                if position >= offset && positions.contains(&(position - offset)) {
+                    format!(
+                        "{}{}{}{}",
+                        self.match_style.prefix(),
+                        c,
+                        RESET,
+                        text_style.prefix()
+                    )
+                } else {
+                    c.to_string()
+                }
+            })
+            .collect()
+    }
+
+    /// Selects or unselects the value under the cursor
+    fn toggle_selection(&mut self) {
+        let index = self.index();
+        if !self.multi_select || index >= self.values.len() {
+            return;
+        }
+
+        match self.selected.iter().position(|selected| *selected == index) {
+            Some(position) => {
+                self.selected.remove(position);
+            }
+            None => self.selected.push(index),
+        }
+    }
+
+    /// Width of the mark in front of the values showing if they are selected
+    fn selection_mark_width(&self) -> usize {
+        if self.multi_select {
+            2
+        } else {
+            0
+        }
+    }
+
+    /// Value as shown in the menu, with the selection mark in multi select mode
+    fn display_value<'a>(&self, index: usize, suggestion: &'a Suggestion) -> Cow<'a, str> {
+        if !self.multi_select {
+            Cow::Borrowed(&suggestion.value)
+        } else if self.selected.contains(&index) {
+            Cow::Owned(format!("* {}", suggestion.value))
+        } else {
+            Cow::Owned(format!("  {}", suggestion.value))
+        }
+    }
+
     /// Move menu cursor to the next element
     fn move_next(&mut self) {
         let mut new_col = self.col_pos + 1;
@@ -229,6 +483,109 @@ impl CompletionMenu {
         }
     }
 
+    /// Move menu cursor one page down, stopping at the last row
+    fn move_next_page(&mut self) {
+        let last_row = self.get_rows().saturating_sub(1);
+        self.row_pos = (self.row_pos + self.page_rows).min(last_row);
+        self.clamp_column();
+    }
+
+    /// Move menu cursor one page up, stopping at the first row
+    fn move_previous_page(&mut self) {
+        self.row_pos = self.row_pos.saturating_sub(self.page_rows);
+    }
+
+    /// Keeps the cursor on a value when moving to an incomplete last row
+    fn clamp_column(&mut self) {
+        let row_values = (self.values.len() as u16).saturating_sub(self.row_pos * self.get_cols());
+        self.col_pos = self.col_pos.min(row_values.saturating_sub(1));
+    }
+
+    /// Screen lines of a row, more than one for a wrapped description
+    fn row_lines(&self, row: u16) -> usize {
+        if self.description_rows == 1 || self.get_cols() > 1 {
+            return 1;
+        }
+        self.values
+            .get(row as usize)
+            .and_then(|suggestion| suggestion.description.as_deref())
+            .map_or(1, |description| self.fit_description(description).len())
+    }
+
+    /// Screen lines of the given rows
+    fn rows_lines(&self, rows: Range<u16>) -> usize {
+        rows.map(|row| self.row_lines(row)).sum()
+    }
+
+    /// Number of lines the menu can use
+    fn visible_lines(&self, available_lines: u16) -> usize {
+        self.max_rows
+            .map_or(available_lines, |max_rows| available_lines.min(max_rows))
+            .max(1) as usize
+    }
+
+    /// First row and number of rows that are shown with the available lines
+    ///
+    /// The rows before the selected one are shown first, so the selected row
+    /// stays visible at the bottom when moving down.
+    fn visible_rows(&self, available_lines: u16) -> (u16, u16) {
+        let lines = self.visible_lines(available_lines);
+
+        let mut first_row = self.row_pos;
+        let mut used_lines = self.row_lines(first_row);
+        while first_row > 0 && used_lines + self.row_lines(first_row - 1) <= lines {
+            first_row -= 1;
+            used_lines += self.row_lines(first_row);
+        }
+        let mut end_row = self.row_pos + 1;
+        while end_row < self.get_rows() && used_lines + self.row_lines(end_row) <= lines {
+            used_lines += self.row_lines(end_row);
+            end_row += 1;
+        }
+
+        (first_row, end_row - first_row)
+    }
+
+    /// Appends the scrollbar column to the visible lines of the menu
+    ///
+    /// Computed in usize, as the products overflow u16 for long lists
+    fn add_scrollbar(
+        &self,
+        menu: &str,
+        first_line: usize,
+        visible_lines: usize,
+        total_lines: usize,
+        use_ansi_coloring: bool,
+    ) -> String {
+        let scroll_range = total_lines.saturating_sub(visible_lines).max(1);
+        let thumb_size = (visible_lines * visible_lines / total_lines).max(1);
+        let thumb_start = (first_line * visible_lines.saturating_sub(thumb_size)
+            + scroll_range / 2)
+            / scroll_range;
+        let row_width = self.get_cols() as usize * self.get_width();
+
+        menu.split("\r\n")
+            .take(visible_lines)
+            .enumerate()
+            .map(|(row, line)| {
+                let bar = if row >= thumb_start && row < thumb_start + thumb_size {
+                    "█"
+                } else {
+                    "│"
+                };
+                let bar = if use_ansi_coloring {
+                    self.color.text_style.paint(bar).to_string()
+                } else {
+                    bar.to_string()
+                };
+                let padding = row_width.saturating_sub(line_width(line));
+
+                format!("{}{:padding$}{}", line, "", bar, padding = padding)
+            })
+            .collect::<Vec<String>>()
+            .join("\r\n")
+    }
+
     /// Menu index based on column and row position
     fn index(&self) -> usize {
         let index = self.row_pos * self.get_cols() + self.col_pos;
@@ -296,6 +653,38 @@ impl CompletionMenu {
         }
     }
 
+    /// Column where the descriptions start, after the longest value
+    fn description_column(&self) -> usize {
+        self.longest_suggestion + self.default_details.col_padding
+    }
+
+    /// Wraps the description onto at most `description_rows` lines of the column
+    ///
+    /// The last line is shortened with an ellipsis if the description doesn't fit.
+    fn fit_description(&self, description: &str) -> Vec<String> {
+        let available_width = self.get_width().saturating_sub(self.description_column());
+
+        // Descriptions spanning several lines are wrapped like one line
+        let description = description.replace('\n', " ");
+        if description.width() <= available_width {
+            return vec![description];
+        }
+
+        let mut lines = wrap_words(&description, available_width);
+        // Values in several columns have no room below them
+        let rows = if self.get_cols() > 1 {
+            1
+        } else {
+            self.description_rows as usize
+        };
+        if lines.len() > rows {
+            let rest = lines.split_off(rows - 1).join(" ");
+            lines.push(truncate_with_ellipsis(&rest, available_width));
+        }
+
+        lines
+    }
+
     /// Creates default string that represents one suggestion from the menu
     fn create_string(
         &self,
@@ -305,46 +694,67 @@ impl CompletionMenu {
         empty_space: usize,
         use_ansi_coloring: bool,
     ) -> String {
-        if use_ansi_coloring {
+        let description_lines = suggestion
+            .description
+            .as_deref()
+            .map(|description| self.fit_description(description))
+            .unwrap_or_default();
+        let description = description_lines.first();
+        let continued_lines = description_lines.get(1..).unwrap_or_default();
+        let value = self.display_value(index, suggestion);
+
+        let row = if use_ansi_coloring {
+            let text_style = if index == self.index() {
+                self.color.selected_text_style
+            } else {
+                self.color.text_style
+            };
+            let highlighted = self.highlight_matches(index, &value, text_style);
+            // The highlighted value contains escape codes, so it is padded by its characters
+            let padding = (self.longest_suggestion + self.default_details.col_padding)
+                .saturating_sub(value.chars().count());
+
             if index == self.index() {
-                if let Some(description) = &suggestion.description {
+                if let Some(description) = &description {
                     format!(
-                        "{}{:max$}{}{}{}",
-                        self.color.selected_text_style.prefix(),
-                        &suggestion.value,
+                        "{}{}{:padding$}{}{}{}",
+                        text_style.prefix(),
+                        highlighted,
+                        "",
                         description,
                         RESET,
                         self.end_of_line(column),
-                        max = self.longest_suggestion + self.default_details.col_padding,
+                        padding = padding,
                     )
                 } else {
                     format!(
                         "{}{}{}{:>empty$}{}",
-                        self.color.selected_text_style.prefix(),
-                        &suggestion.value,
+                        text_style.prefix(),
+                        highlighted,
                         RESET,
                         "",
                         self.end_of_line(column),
                         empty = empty_space,
                     )
                 }
-            } else if let Some(description) = &suggestion.description {
+            } else if let Some(description) = &description {
                 format!(
-                    "{}{:max$}{}{}{}{}{}",
-                    self.color.text_style.prefix(),
-                    &suggestion.value,
+                    "{}{}{:padding$}{}{}{}{}{}",
+                    text_style.prefix(),
+                    highlighted,
+                    "",
                     RESET,
                     self.color.description_style.prefix(),
                     description,
                     RESET,
                     self.end_of_line(column),
-                    max = self.longest_suggestion + self.default_details.col_padding,
+                    padding = padding,
                 )
             } else {
                 format!(
                     "{}{}{}{}{:>empty$}{}{}",
-                    self.color.text_style.prefix(),
-                    &suggestion.value,
+                    text_style.prefix(),
+                    highlighted,
                     RESET,
                     self.color.description_style.prefix(),
                     "",
@@ -357,11 +767,11 @@ impl CompletionMenu {
             // If no ansi coloring is found, then the selection word is the line in uppercase
             let marker = if index == self.index() { ">" } else { "" };
 
-            let line = if let Some(description) = &suggestion.description {
+            let line = if let Some(description) = &description {
                 format!(
                     "{}{:max$}{}{}",
                     marker,
-                    &suggestion.value,
+                    &value,
                     description,
                     self.end_of_line(column),
                     max = self.longest_suggestion
@@ -374,7 +784,7 @@ impl CompletionMenu {
                 format!(
                     "{}{}{:>empty$}{}",
                     marker,
-                    &suggestion.value,
+                    &value,
                     "",
                     self.end_of_line(column),
                     empty = empty_space.saturating_sub(marker.len()),
@@ -386,8 +796,109 @@ impl CompletionMenu {
             } else {
                 line
             }
+        };
+
+        row + &self.continued_description(continued_lines, index, use_ansi_coloring)
+    }
+
+    /// Lines of a wrapped description after the first one, starting at the description column
+    fn continued_description(
+        &self,
+        lines: &[String],
+        index: usize,
+        use_ansi_coloring: bool,
+    ) -> String {
+        let selected = index == self.index();
+
+        lines
+            .iter()
+            .map(|line| {
+                if use_ansi_coloring {
+                    let style = if selected {
+                        self.color.selected_text_style
+                    } else {
+                        self.color.description_style
+                    };
+                    format!(
+                        "{:indent$}{}{}{}\r\n",
+                        "",
+                        style.prefix(),
+                        line,
+                        RESET,
+                        indent = self.description_column(),
+                    )
+                } else if selected {
+                    format!(
+                        "{:indent$}{}\r\n",
+                        "",
+                        line.to_uppercase(),
+                        indent = self.description_column()
+                    )
+                } else {
+                    format!(
+                        "{:indent$}{}\r\n",
+                        "",
+                        line,
+                        indent = self.description_column()
+                    )
+                }
+            })
+            .collect()
+    }
+}
+
+/// Splits the text into lines of at most `width` columns between its words
+///
+/// Words longer than a line are split between their characters.
+fn wrap_words(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        if line.is_empty() && word.width() <= width {
+            line.push_str(word);
+            continue;
+        } else if !line.is_empty() && line.width() + 1 + word.width() <= width {
+            line.push(' ');
+            line.push_str(word);
+            continue;
+        }
+
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        let mut line_width = 0;
+        for c in word.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if line_width + char_width > width && !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0;
+            }
+            line.push(c);
+            line_width += char_width;
         }
     }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Shortens the text with an ellipsis to fit in `width` columns
+fn truncate_with_ellipsis(text: &str, width: usize) -> String {
+    let mut used_width = 0;
+    let mut truncated: String = text
+        .chars()
+        .take_while(|c| {
+            used_width += c.width().unwrap_or(0);
+            used_width < width
+        })
+        .collect();
+    truncated.push('…');
+
+    truncated
 }
 
 impl Menu for CompletionMenu {
@@ -461,6 +972,7 @@ impl Menu for CompletionMenu {
     fn menu_event(&mut self, event: MenuEvent) {
         if let MenuEvent::Activate(_) = event {
             self.active = true;
+            self.cache = None;
         }
 
         self.event = Some(event);
@@ -479,8 +991,33 @@ impl Menu for CompletionMenu {
         // Also, by replacing the new line character with a space, the insert
         // position is maintain in the line buffer.
         let trimmed_buffer = line_buffer.get_buffer().replace('\n', " ");
-        self.values = completer.complete(trimmed_buffer.as_str(), line_buffer.insertion_point());
+        let position = line_buffer.insertion_point();
+
+        if !self.incremental_filtering {
+            self.values = completer.complete(trimmed_buffer.as_str(), position);
+        } else if let Some((values, match_positions)) =
+            self.narrow_cached_values(&trimmed_buffer, position)
+        {
+            self.values = values;
+            self.match_positions = match_positions;
+        } else {
+            self.values = completer.complete(trimmed_buffer.as_str(), position);
+            self.match_positions = self
+                .values
+                .iter()
+                .map(|suggestion| {
+                    self.typed_positions(&trimmed_buffer, position, suggestion)
+                        .unwrap_or_default()
+                })
+                .collect();
+            self.cache = Some(CompletionCache {
+                buffer: trimmed_buffer,
+                position,
+                values: self.values.clone(),
+            });
+        }
         self.reset_position();
+        self.selected.clear();
     }
 
     /// The working details for the menu changes based on the size of the lines
@@ -493,6 +1030,16 @@ impl Menu for CompletionMenu {
         painter: &Painter,
     ) {
         if let Some(event) = self.event.take() {
+            // The scrollbar takes the last column of the screen
+            let screen_width = if self.scrollbar {
+                painter.screen_width().saturating_sub(1)
+            } else {
+                painter.screen_width()
+            };
+            // The menu is printed below the prompt line
+            let (_, page_rows) = self.visible_rows(painter.remaining_lines().saturating_sub(1));
+            self.page_rows = page_rows;
+
             // The working value for the menu are updated first before executing any of the
             // menu events
             //
@@ -505,18 +1052,21 @@ impl Menu for CompletionMenu {
 
             if exist_description {
                 self.working_details.columns = 1;
-                self.working_details.col_width = painter.screen_width() as usize;
+                self.working_details.col_width = screen_width as usize;
 
                 self.longest_suggestion = self.get_values().iter().fold(0, |prev, suggestion| {
-                    if prev >= suggestion.value.len() {
+                    let str_len = suggestion.value.len() + self.selection_mark_width();
+                    if prev >= str_len {
                         prev
                     } else {
-                        suggestion.value.len()
+                        str_len
                     }
                 });
             } else {
                 let max_width = self.get_values().iter().fold(0, |acc, suggestion| {
-                    let str_len = suggestion.value.len() + self.default_details.col_padding;
+                    let str_len = suggestion.value.len()
+                        + self.selection_mark_width()
+                        + self.default_details.col_padding;
                     if str_len > acc {
                         str_len
                     } else {
@@ -529,7 +1079,7 @@ impl Menu for CompletionMenu {
                 let default_width = if let Some(col_width) = self.default_details.col_width {
                     col_width
                 } else {
-                    let col_width = painter.screen_width() / self.default_details.columns;
+                    let col_width = screen_width / self.default_details.columns;
                     col_width as usize
                 };
 
@@ -543,7 +1093,7 @@ impl Menu for CompletionMenu {
 
                 // The working columns is adjusted based on possible number of columns
                 // that could be fitted in the screen with the calculated column width
-                let possible_cols = painter.screen_width() / self.working_details.col_width as u16;
+                let possible_cols = screen_width / self.working_details.col_width as u16;
                 if possible_cols > self.default_details.columns {
                     self.working_details.columns = self.default_details.columns.max(1);
                 } else {
@@ -560,7 +1110,10 @@ impl Menu for CompletionMenu {
                         self.update_values(line_buffer, history, completer);
                     }
                 }
-                MenuEvent::Deactivate => self.active = false,
+                MenuEvent::Deactivate => {
+                    self.active = false;
+                    self.selected.clear();
+                }
                 MenuEvent::Edit(updated) => {
                     self.reset_position();
 
@@ -574,24 +1127,48 @@ impl Menu for CompletionMenu {
                 MenuEvent::MoveDown => self.move_down(),
                 MenuEvent::MoveLeft => self.move_left(),
                 MenuEvent::MoveRight => self.move_right(),
-                MenuEvent::PreviousPage | MenuEvent::NextPage => {
-                    // The completion menu doest have the concept of pages, yet
-                }
+                MenuEvent::NextPage => self.move_next_page(),
+                MenuEvent::PreviousPage => self.move_previous_page(),
+                MenuEvent::ToggleSelection => self.toggle_selection(),
             }
         }
     }
 
     /// The buffer gets replaced in the Span location
+    ///
+    /// In multi select mode the selected values are inserted joined by the separator
     fn replace_in_buffer(&self, line_buffer: &mut LineBuffer) {
-        if let Some(Suggestion { value, span, .. }) = self.get_value() {
-            let mut offset = line_buffer.insertion_point();
-            offset += value.len() - (span.end - span.start);
-
-            line_buffer.replace(span.start..span.end, &value);
-            line_buffer.set_insertion_point(offset);
+        let selected: Vec<&Suggestion> = self
+            .selected
+            .iter()
+            .filter_map(|index| self.values.get(*index))
+            .collect();
+
+        match selected.first() {
+            Some(first) => {
+                let suggestion = Suggestion {
+                    value: selected
+                        .iter()
+                        .map(|suggestion| suggestion.value.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(&self.selection_separator),
+                    description: None,
+                    span: first.span,
+                };
+                replace_in_buffer(&suggestion, line_buffer);
+            }
+            None => {
+                if let Some(suggestion) = self.get_value() {
+                    replace_in_buffer(&suggestion, line_buffer);
+                }
+            }
         }
     }
 
+    fn multi_select(&self) -> bool {
+        self.multi_select
+    }
+
     /// Minimum rows that should be displayed by the menu
     fn min_rows(&self) -> u16 {
         self.get_rows().min(self.min_rows)
@@ -603,7 +1180,8 @@ impl Menu for CompletionMenu {
     }
 
     fn menu_required_lines(&self, _terminal_columns: u16) -> u16 {
-        self.get_rows()
+        let lines = self.rows_lines(0..self.get_rows()).min(u16::MAX as usize) as u16;
+        self.max_rows.map_or(lines, |max_rows| lines.min(max_rows))
     }
 
     fn menu_string(&self, available_lines: u16, use_ansi_coloring: bool) -> String {
@@ -612,18 +1190,15 @@ impl Menu for CompletionMenu {
         } else {
             // The skip values represent the number of lines that should be skipped
             // while printing the menu
-            let skip_values = if self.row_pos >= available_lines {
-                let skip_lines = self.row_pos.saturating_sub(available_lines) + 1;
-                (skip_lines * self.get_cols()) as usize
-            } else {
-                0
-            };
+            let (first_row, visible_rows) = self.visible_rows(available_lines);
+            let skip_values = (first_row * self.get_cols()) as usize;
 
             // It seems that crossterm prefers to have a complete string ready to be printed
             // rather than looping through the values and printing multiple things
             // This reduces the flickering when printing the menu
-            let available_values = (available_lines * self.get_cols()) as usize;
-            self.get_values()
+            let available_values = (visible_rows * self.get_cols()) as usize;
+            let menu: String = self
+                .get_values()
                 .iter()
                 .skip(skip_values)
                 .take(available_values)
@@ -632,11 +1207,396 @@ impl Menu for CompletionMenu {
                     // Correcting the enumerate index based on the number of skipped values
                     let index = index + skip_values;
                     let column = index as u16 % self.get_cols();
-                    let empty_space = self.get_width().saturating_sub(suggestion.value.len());
+                    let empty_space = self
+                        .get_width()
+                        .saturating_sub(suggestion.value.len() + self.selection_mark_width());
 
                     self.create_string(suggestion, index, column, empty_space, use_ansi_coloring)
                 })
-                .collect()
+                .collect();
+
+            // A selected value with a long description can be taller than the menu
+            let max_lines = self.visible_lines(available_lines);
+            let visible_lines = self.rows_lines(first_row..first_row + visible_rows);
+            let (menu, visible_lines) = if visible_lines > max_lines {
+                let menu = menu.split_inclusive("\r\n").take(max_lines).collect();
+                (menu, max_lines)
+            } else {
+                (menu, visible_lines)
+            };
+            let total_lines = self.rows_lines(0..self.get_rows());
+            if self.scrollbar && total_lines > visible_lines {
+                self.add_scrollbar(
+                    &menu,
+                    self.rows_lines(0..first_row),
+                    visible_lines,
+                    total_lines,
+                    use_ansi_coloring,
+                )
+            } else {
+                menu
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{DefaultCompleter, FileBackedHistory, Span};
+    use pretty_assertions::assert_eq;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn multi_select_inserts_joined_selection() {
+        let completer = DefaultCompleter::new(vec!["foobar".into(), "food".into(), "bar".into()]);
+        let history = FileBackedHistory::default();
+        let mut painter = Painter::new(std::io::BufWriter::new(std::io::stderr()));
+        painter.handle_resize(80, 24);
+        let mut line_buffer = LineBuffer::new();
+        line_buffer.insert_str("cat fo");
+
+        let mut menu = CompletionMenu::default()
+            .with_multi_select(true)
+            .with_selection_separator(" ".into());
+        for event in [
+            MenuEvent::Activate(false),
+            MenuEvent::ToggleSelection,
+            MenuEvent::NextElement,
+            MenuEvent::ToggleSelection,
+        ] {
+            menu.menu_event(event);
+            menu.update_working_details(&mut line_buffer, &history, &completer, &painter);
+        }
+
+        let menu_string = menu.menu_string(5, false);
+        assert!(menu_string.starts_with("* foobar"));
+        assert!(menu_string.contains(">* FOOD"));
+
+        menu.replace_in_buffer(&mut line_buffer);
+        assert_eq!(line_buffer.get_buffer(), "cat foobar food");
+        assert_eq!(line_buffer.insertion_point(), 15);
+    }
+
+    struct CountingCompleter {
+        completer: DefaultCompleter,
+        calls: AtomicUsize,
+    }
+
+    impl Completer for CountingCompleter {
+        fn complete(&self, line: &str, pos: usize) -> Vec<Suggestion> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.completer.complete(line, pos)
+        }
+    }
+
+    fn values(menu: &CompletionMenu) -> Vec<&str> {
+        menu.values
+            .iter()
+            .map(|suggestion| suggestion.value.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn typing_narrows_and_deleting_widens_cached_values() {
+        let completer = CountingCompleter {
+            completer: DefaultCompleter::new(vec!["foobar".into(), "food".into(), "fox".into()]),
+            calls: AtomicUsize::new(0),
+        };
+        let history = FileBackedHistory::default();
+        let mut painter = Painter::new(std::io::BufWriter::new(std::io::stderr()));
+        painter.handle_resize(80, 24);
+        let mut line_buffer = LineBuffer::new();
+        line_buffer.insert_str("cat fo");
+
+        let mut menu = CompletionMenu::default().with_incremental_filtering(true);
+        let mut update =
+            |menu: &mut CompletionMenu, event: MenuEvent, line_buffer: &mut LineBuffer| {
+                menu.menu_event(event);
+                menu.update_working_details(line_buffer, &history, &completer, &painter);
+            };
+
+        update(&mut menu, MenuEvent::Activate(false), &mut line_buffer);
+        assert_eq!(values(&menu), vec!["foobar", "food", "fox"]);
+
+        line_buffer.insert_char('o');
+        update(&mut menu, MenuEvent::Edit(false), &mut line_buffer);
+        assert_eq!(values(&menu), vec!["foobar", "food"]);
+        assert_eq!(menu.values[0].span, Span::new(4, 7));
+
+        line_buffer.insert_char('d');
+        update(&mut menu, MenuEvent::Edit(false), &mut line_buffer);
+        assert_eq!(values(&menu), vec!["food"]);
+
+        line_buffer.delete_left_grapheme();
+        line_buffer.delete_left_grapheme();
+        update(&mut menu, MenuEvent::Edit(false), &mut line_buffer);
+        assert_eq!(values(&menu), vec!["foobar", "food", "fox"]);
+        assert_eq!(completer.calls.load(Ordering::SeqCst), 1);
+
+        // Deleting past the position of the completer call asks the completer again
+        line_buffer.delete_left_grapheme();
+        update(&mut menu, MenuEvent::Edit(false), &mut line_buffer);
+        assert_eq!(values(&menu), vec!["foobar", "food", "fox"]);
+        assert_eq!(completer.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn narrowed_values_equal_a_new_completer_call() {
+        let completer = DefaultCompleter::new(vec![
+            "foobar".into(),
+            "fobnicate".into(),
+            "fox".into(),
+            "format".into(),
+        ]);
+        let history = FileBackedHistory::default();
+        let mut line_buffer = LineBuffer::new();
+        line_buffer.insert_str("cat fo");
+
+        let mut menu = CompletionMenu::default().with_incremental_filtering(true);
+        menu.update_values(&mut line_buffer, &history, &completer);
+
+        for c in ['b', 'n'] {
+            line_buffer.insert_char(c);
+            menu.update_values(&mut line_buffer, &history, &completer);
+
+            let fresh = completer.complete(line_buffer.get_buffer(), line_buffer.insertion_point());
+            assert_eq!(menu.values, fresh);
+        }
+        assert_eq!(values(&menu), vec!["fobnicate"]);
+    }
+
+    #[test]
+    fn fuzzy_filtering_keeps_subsequence_matches() {
+        let completer = DefaultCompleter::new(vec!["foobar".into(), "fox".into()]);
+        let history = FileBackedHistory::default();
+        let mut line_buffer = LineBuffer::new();
+        line_buffer.insert_str("fo");
+
+        let mut menu = CompletionMenu::default()
+            .with_incremental_filtering(true)
+            .with_fuzzy_filtering(true);
+        menu.update_values(&mut line_buffer, &history, &completer);
+        line_buffer.insert_char('b');
+        menu.update_values(&mut line_buffer, &history, &completer);
+
+        assert_eq!(values(&menu), vec!["foobar"]);
+        assert_eq!(menu.match_positions, vec![vec![0, 1, 3]]);
+    }
+
+    #[test]
+    fn completer_is_called_when_cached_values_dont_match() {
+        let completer = CountingCompleter {
+            completer: DefaultCompleter::new(vec!["foobar".into(), "fox".into()]),
+            calls: AtomicUsize::new(0),
+        };
+        let history = FileBackedHistory::default();
+        let mut line_buffer = LineBuffer::new();
+        line_buffer.insert_str("fo");
+
+        let mut menu = CompletionMenu::default().with_incremental_filtering(true);
+        menu.update_values(&mut line_buffer, &history, &completer);
+        line_buffer.insert_str("ol");
+        menu.update_values(&mut line_buffer, &history, &completer);
+
+        assert!(menu.values.is_empty());
+        assert_eq!(completer.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn typed_characters_are_highlighted() {
+        let completer = DefaultCompleter::new(vec!["foobar".into(), "bar".into()]);
+        let history = FileBackedHistory::default();
+        let mut line_buffer = LineBuffer::new();
+        line_buffer.insert_str("fo");
+
+        let mut menu = CompletionMenu::default()
+            .with_incremental_filtering(true)
+            .with_match_style(Style::new().underline());
+        menu.update_values(&mut line_buffer, &history, &completer);
+
+        let text_style = menu.color.selected_text_style;
+        assert_eq!(
+            menu.highlight_matches(0, "foobar", text_style),
+            format!(
+                "{0}f{1}{2}{0}o{1}{2}obar",
+                Style::new().underline().prefix(),
+                RESET,
+                text_style.prefix()
+            )
+        );
+    }
+
+    #[test]
+    fn toggle_is_ignored_in_single_select_mode() {
+        let mut menu = menu_with_values(3);
+        menu.toggle_selection();
+        menu.move_next();
+
+        let mut line_buffer = LineBuffer::new();
+        menu.replace_in_buffer(&mut line_buffer);
+        assert_eq!(line_buffer.get_buffer(), "v1");
+    }
+
+    fn menu_with_values(count: usize) -> CompletionMenu {
+        let mut menu = CompletionMenu::default().with_scrollbar(true);
+        menu.values = (0..count)
+            .map(|i| Suggestion {
+                value: format!("v{}", i),
+                description: None,
+                span: Span::new(0, 0),
+            })
+            .collect();
+        menu.working_details = ColumnDetails {
+            columns: 1,
+            col_width: 4,
+        };
+        menu.page_rows = 3;
+        menu
+    }
+
+    #[test]
+    fn scrollbar_of_long_list_does_not_overflow() {
+        let mut menu = menu_with_values(5000);
+        menu.page_rows = 20;
+
+        menu.row_pos = 4999;
+        let menu_string = menu.menu_string(20, false);
+        let bars: String = menu_string
+            .split("\r\n")
+            .map(|line| line.chars().last().unwrap())
+            .collect();
+        assert_eq!(bars, format!("{}█", "│".repeat(19)));
+
+        // The selection is in the last visible row, so rows 2481 to 2500 are shown
+        menu.row_pos = 2500;
+        let menu_string = menu.menu_string(20, false);
+        let thumb_row = menu_string
+            .split("\r\n")
+            .position(|line| line.ends_with('█'));
+        assert_eq!(thumb_row, Some(9));
+    }
+
+    #[test]
+    fn viewport_follows_selection_and_scrollbar_moves() {
+        let mut menu = menu_with_values(10);
+        assert_eq!(menu.menu_string(3, false), ">V0 █\r\nv1  │\r\nv2  │");
+
+        for _ in 0..4 {
+            menu.move_next();
+        }
+        assert_eq!(menu.menu_string(3, false), "v2  │\r\nv3  █\r\n>V4 │");
+
+        menu.move_next_page();
+        assert_eq!(menu.row_pos, 7);
+        assert_eq!(menu.menu_string(3, false), "v5  │\r\nv6  █\r\n>V7 │");
+
+        menu.move_next_page();
+        assert_eq!(menu.row_pos, 9);
+        assert_eq!(menu.menu_string(3, false), "v7  │\r\nv8  │\r\n>V9 █");
+        menu.move_previous_page();
+        assert_eq!(menu.row_pos, 6);
+    }
+
+    #[test]
+    fn paging_clamps_at_the_edges() {
+        let mut menu = menu_with_values(7);
+        menu.working_details.columns = 3;
+
+        menu.col_pos = 2;
+        menu.move_next_page();
+        assert_eq!((menu.row_pos, menu.col_pos), (2, 0));
+
+        menu.move_previous_page();
+        menu.move_previous_page();
+        assert_eq!((menu.row_pos, menu.col_pos), (0, 0));
+    }
+
+    #[test]
+    fn max_rows_limits_required_lines() {
+        let menu = menu_with_values(10).with_max_rows(4);
+
+        assert_eq!(menu.menu_required_lines(80), 4);
+        assert_eq!(menu.menu_string(20, false).split("\r\n").count(), 4);
+    }
+
+    #[test]
+    fn long_descriptions_are_truncated_to_the_column() {
+        let mut menu = menu_with_values(0);
+        menu.values = vec![
+            Suggestion {
+                value: "ls".into(),
+                description: Some("list directory contents".into()),
+                span: Span::new(0, 0),
+            },
+            Suggestion {
+                value: "cd".into(),
+                description: Some("change\ndirectory".into()),
+                span: Span::new(0, 0),
+            },
+        ];
+        menu.working_details.col_width = 14;
+        menu.longest_suggestion = 2;
+
+        assert_eq!(
+            menu.menu_string(3, false),
+            ">LS LIST DIRE…\r\ncd  change di…\r\n"
+        );
+    }
+
+    #[test]
+    fn long_descriptions_are_wrapped_onto_rows() {
+        let mut menu = menu_with_values(0)
+            .with_scrollbar(false)
+            .with_description_rows(2);
+        menu.values = vec![
+            Suggestion {
+                value: "ls".into(),
+                description: Some("list directory contents".into()),
+                span: Span::new(0, 0),
+            },
+            Suggestion {
+                value: "cd".into(),
+                description: Some("change\ndirectory".into()),
+                span: Span::new(0, 0),
+            },
+        ];
+        menu.working_details.col_width = 14;
+        menu.longest_suggestion = 2;
+
+        assert_eq!(menu.menu_required_lines(80), 4);
+        assert_eq!(
+            menu.menu_string(5, false),
+            ">LS LIST\r\n    DIRECTORY…\r\ncd  change\r\n    directory\r\n"
+        );
+
+        // Only the selected value fits in three lines
+        menu.move_next();
+        assert_eq!(menu.visible_rows(3), (1, 1));
+        assert_eq!(
+            menu.menu_string(3, false),
+            ">CD CHANGE\r\n    DIRECTORY\r\n"
+        );
+        assert_eq!(menu.menu_string(1, false), ">CD CHANGE\r\n");
+    }
+
+    #[test]
+    fn words_longer_than_a_line_are_split() {
+        assert_eq!(wrap_words("ab abcdefg", 4), ["ab", "abcd", "efg"]);
+    }
+
+    #[test]
+    fn suggestions_without_description_are_not_changed() {
+        let mut menu = menu_with_values(2);
+        menu.working_details.col_width = 2;
+
+        assert_eq!(menu.menu_string(3, false), ">V0\r\nv1\r\n");
+    }
+
+    #[test]
+    fn no_scrollbar_when_everything_fits() {
+        let menu = menu_with_values(2);
+
+        assert_eq!(menu.menu_string(3, false), ">V0 \r\nv1  \r\n");
+    }
+}
@@ -1,13 +1,73 @@
 use super::{
-    menu_functions::{parse_selection_char, string_difference},
+    menu_functions::{parse_selection_char, replace_in_buffer, string_difference},
     Menu, MenuEvent, MenuTextStyle,
 };
 use crate::{
     painting::{estimate_single_line_wraps, Painter},
-    Completer, History, LineBuffer, Span, Suggestion,
+    Completer, FuzzyMatch, History, LineBuffer, PromptHistorySearch, PromptHistorySearchStatus,
+    Span, Suggestion,
 };
-use nu_ansi_term::{ansi::RESET, Style};
-use std::iter::Sum;
+use nu_ansi_term::{ansi::RESET, Color, Style};
+use std::{iter::Sum, ops::Range};
+
+/// How the history menu matches the text typed after it was activated
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum HistorySearchMode {
+    /// Entries containing the typed text (default)
+    #[default]
+    Substring,
+    /// Entries containing the typed characters in order, best matches first
+    Fuzzy,
+}
+
+impl HistorySearchMode {
+    /// Byte ranges of `entry` matched by `term`
+    fn match_ranges(&self, term: &str, entry: &str) -> Vec<Range<usize>> {
+        if term.is_empty() {
+            return Vec::new();
+        }
+
+        match self {
+            HistorySearchMode::Substring => entry
+                .match_indices(term)
+                .map(|(index, matched)| index..index + matched.len())
+                .collect(),
+            HistorySearchMode::Fuzzy => FuzzyMatch::find(term, entry)
+                .map(|fuzzy_match| {
+                    fuzzy_match
+                        .positions
+                        .into_iter()
+                        .map(|index| {
+                            let len = entry[index..].chars().next().map_or(0, char::len_utf8);
+                            index..index + len
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// History entries matching `term`, most relevant first
+    fn query(&self, term: &str, history: &dyn History) -> Vec<String> {
+        match self {
+            HistorySearchMode::Substring => history.query_entries(term),
+            HistorySearchMode::Fuzzy => {
+                let mut matches: Vec<(i64, String)> = history
+                    .iter_chronologic()
+                    .rev()
+                    .filter_map(|entry| {
+                        FuzzyMatch::find(term, entry)
+                            .map(|fuzzy_match| (fuzzy_match.score, entry.clone()))
+                    })
+                    .collect();
+
+                // Stable sort, so that newer entries stay first for equal scores
+                matches.sort_by(|(score_a, _), (score_b, _)| score_b.cmp(score_a));
+                matches.into_iter().map(|(_, entry)| entry).collect()
+            }
+        }
+    }
+}
 
 struct Page {
     size: usize,
@@ -37,6 +97,12 @@ impl<'a> Sum<&'a Page> for Page {
 pub struct HistoryMenu {
     /// Menu coloring
     color: MenuTextStyle,
+    /// Text style for the parts of an entry matched by the search
+    match_style: Style,
+    /// How the typed text is matched against the history
+    search_mode: HistorySearchMode,
+    /// Text typed after the menu was activated that is used to query the history
+    search_term: String,
     /// Number of history records pulled until page is full
     page_size: usize,
     /// Menu marker displayed when the menu is active
@@ -74,6 +140,9 @@ impl Default for HistoryMenu {
     fn default() -> Self {
         Self {
             color: MenuTextStyle::default(),
+            match_style: Color::Yellow.bold(),
+            search_mode: HistorySearchMode::default(),
+            search_term: String::new(),
             page_size: 10,
             selection_char: '!',
             active: false,
@@ -106,7 +175,21 @@ impl HistoryMenu {
         self
     }
 
-    /// Menu builder with page size
+    /// Menu builder with new value for the style of the matched text
+    #[must_use]
+    pub fn with_match_style(mut self, match_style: Style) -> Self {
+        self.match_style = match_style;
+        self
+    }
+
+    /// Menu builder with search mode
+    #[must_use]
+    pub fn with_search_mode(mut self, search_mode: HistorySearchMode) -> Self {
+        self.search_mode = search_mode;
+        self
+    }
+
+    /// Menu builder with page size, the number of results shown at once
     #[must_use]
     pub fn with_page_size(mut self, page_size: usize) -> Self {
         self.page_size = page_size;
@@ -287,6 +370,25 @@ impl HistoryMenu {
         }
     }
 
+    /// Entry with the parts matched by the search highlighted
+    fn highlight_matches(&self, entry: &str, index: usize) -> String {
+        let mut highlighted = String::new();
+        let mut last = 0;
+        for range in self.search_mode.match_ranges(&self.search_term, entry) {
+            highlighted.push_str(&entry[last..range.start]);
+            highlighted.push_str(&format!(
+                "{}{}{}{}",
+                self.match_style.prefix(),
+                &entry[range.clone()],
+                RESET,
+                self.text_style(index)
+            ));
+            last = range.end;
+        }
+        highlighted.push_str(&entry[last..]);
+        highlighted
+    }
+
     /// Creates default string that represents one line from a menu
     fn create_string(
         &self,
@@ -359,6 +461,7 @@ impl Menu for HistoryMenu {
             MenuEvent::Deactivate => {
                 self.active = false;
                 self.input = None;
+                self.search_term.clear();
             }
             _ => {}
         }
@@ -387,12 +490,13 @@ impl Menu for HistoryMenu {
             self.reset_position();
         }
 
+        self.search_term = parsed.remainder.to_string();
         let values = if parsed.remainder.is_empty() {
             self.history_size = Some(history.max_values());
             self.create_values_no_query(history)
         } else {
             self.history_size = None;
-            history.query_entries(parsed.remainder)
+            self.search_mode.query(parsed.remainder, history)
         };
 
         self.values = values
@@ -441,12 +545,8 @@ impl Menu for HistoryMenu {
 
     /// The buffer gets cleared with the actual value
     fn replace_in_buffer(&self, line_buffer: &mut LineBuffer) {
-        if let Some(Suggestion { value, span, .. }) = self.get_value() {
-            line_buffer.replace(span.start..span.end, &value);
-
-            let mut offset = line_buffer.insertion_point();
-            offset += value.len() - (span.end - span.start);
-            line_buffer.set_insertion_point(offset);
+        if let Some(suggestion) = self.get_value() {
+            replace_in_buffer(&suggestion, line_buffer);
         }
     }
 
@@ -535,6 +635,7 @@ impl Menu for HistoryMenu {
                         self.update_values(line_buffer, history, completer);
                     }
                 }
+                MenuEvent::ToggleSelection => {}
                 MenuEvent::PreviousPage => {
                     match self.page.checked_sub(1) {
                         Some(page_num) => self.page = page_num,
@@ -568,7 +669,11 @@ impl Menu for HistoryMenu {
                     .enumerate()
                     .map(|(index, suggestion)| {
                         // Final string with colors
-                        let line = &suggestion.value;
+                        let line = if use_ansi_coloring {
+                            self.highlight_matches(&suggestion.value, index)
+                        } else {
+                            suggestion.value.clone()
+                        };
                         let line = if line.lines().count() > self.max_lines as usize {
                             let lines = line
                                 .lines()
@@ -601,6 +706,21 @@ impl Menu for HistoryMenu {
     fn min_rows(&self) -> u16 {
         self.max_lines + 1
     }
+
+    /// While text is typed the menu shows the prompt's history search indicator
+    fn history_search(&self) -> Option<PromptHistorySearch> {
+        if self.search_term.is_empty() {
+            return None;
+        }
+
+        let status = if self.values.is_empty() {
+            PromptHistorySearchStatus::Failing
+        } else {
+            PromptHistorySearchStatus::Passing
+        };
+
+        Some(PromptHistorySearch::new(status, self.search_term.clone()))
+    }
 }
 
 fn number_of_lines(entry: &str, max_lines: usize, terminal_columns: u16) -> u16 {
@@ -629,6 +749,107 @@ fn number_of_lines(entry: &str, max_lines: usize, terminal_columns: u16) -> u16
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{DefaultCompleter, FileBackedHistory};
+    use pretty_assertions::assert_eq;
+
+    fn history(entries: &[&str]) -> FileBackedHistory {
+        let mut history = FileBackedHistory::default();
+        for entry in entries {
+            history.append(entry);
+        }
+        history
+    }
+
+    // Feeds the menu the same events the engine sends when typing `typed` after
+    // activating it and moving `moves` rows down
+    fn select_match(
+        menu: &mut HistoryMenu,
+        history: &FileBackedHistory,
+        typed: &str,
+        moves: usize,
+    ) -> LineBuffer {
+        let completer = DefaultCompleter::default();
+        let mut painter = Painter::new(std::io::BufWriter::new(std::io::stderr()));
+        painter.handle_resize(80, 24);
+        let mut line_buffer = LineBuffer::new();
+
+        menu.menu_event(MenuEvent::Activate(false));
+        menu.update_working_details(&mut line_buffer, history, &completer, &painter);
+
+        line_buffer.insert_str(typed);
+        menu.menu_event(MenuEvent::Edit(false));
+        menu.update_working_details(&mut line_buffer, history, &completer, &painter);
+
+        for _ in 0..moves {
+            menu.menu_event(MenuEvent::MoveDown);
+            menu.update_working_details(&mut line_buffer, history, &completer, &painter);
+        }
+
+        menu.replace_in_buffer(&mut line_buffer);
+        line_buffer
+    }
+
+    #[test]
+    fn substring_search_accepts_second_match() {
+        let history = history(&["cd /tmp", "ls", "cd ~/src", "echo cd"]);
+        let mut menu = HistoryMenu::default();
+
+        let line_buffer = select_match(&mut menu, &history, "cd", 1);
+
+        assert_eq!(line_buffer.get_buffer(), "cd ~/src");
+        let history_search = menu.history_search().unwrap();
+        assert_eq!(history_search.term, "cd");
+        assert!(matches!(
+            history_search.status,
+            PromptHistorySearchStatus::Passing
+        ));
+    }
+
+    #[test]
+    fn fuzzy_search_ranks_matches() {
+        let history = history(&["git checkout main", "git commit", "cargo check"]);
+        let mut menu = HistoryMenu::default().with_search_mode(HistorySearchMode::Fuzzy);
+
+        let line_buffer = select_match(&mut menu, &history, "gco", 1);
+
+        assert_eq!(line_buffer.get_buffer(), "git checkout main");
+    }
+
+    #[test]
+    fn failing_search_is_reported() {
+        let history = history(&["ls"]);
+        let mut menu = HistoryMenu::default();
+
+        select_match(&mut menu, &history, "cd", 0);
+
+        assert!(matches!(
+            menu.history_search().map(|search| search.status),
+            Some(PromptHistorySearchStatus::Failing)
+        ));
+    }
+
+    #[test]
+    fn matches_are_highlighted() {
+        let menu = HistoryMenu {
+            search_term: "cd".into(),
+            ..HistoryMenu::default()
+        };
+        let text_style = menu.color.text_style.prefix().to_string();
+
+        assert_eq!(
+            menu.highlight_matches("echo cd", 1),
+            format!(
+                "echo {}cd{}{}",
+                Color::Yellow.bold().prefix(),
+                RESET,
+                text_style
+            )
+        );
+        assert_eq!(
+            HistorySearchMode::Fuzzy.match_ranges("gco", "git checkout"),
+            vec![0..1, 4..5, 9..10]
+        );
+    }
 
     #[test]
     fn number_of_lines_test() {
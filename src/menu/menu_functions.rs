@@ -1,5 +1,5 @@
 //! Collection of common functions that can be used to create menus
-use crate::Suggestion;
+use crate::{LineBuffer, Suggestion};
 
 /// Index result obtained from parsing a string with an index marker
 /// For example, the next string:
@@ -246,9 +246,79 @@ pub fn string_difference<'a>(new_string: &'a str, old_string: &str) -> (usize, &
     }
 }
 
+/// Replaces the span declared by the suggestion with its value
+///
+/// Only the text inside the span is replaced, even if the cursor is in the middle
+/// of a word. The cursor is moved to the end of the inserted text.
+///
+/// ## Example usage
+/// ```
+/// use reedline::{menu_functions::replace_in_buffer, LineBuffer, Span, Suggestion};
+///
+/// let mut line_buffer = LineBuffer::new();
+/// line_buffer.insert_str("git chkout main");
+/// line_buffer.set_insertion_point(6);
+///
+/// let suggestion = Suggestion {
+///     value: "checkout".into(),
+///     description: None,
+///     span: Span::new(4, 10),
+/// };
+/// replace_in_buffer(&suggestion, &mut line_buffer);
+///
+/// assert_eq!(line_buffer.get_buffer(), "git checkout main");
+/// assert_eq!(line_buffer.insertion_point(), 12);
+/// ```
+pub fn replace_in_buffer(suggestion: &Suggestion, line_buffer: &mut LineBuffer) {
+    // A span reaching past the buffer is cut at its end
+    let end = suggestion.span.end.min(line_buffer.get_buffer().len());
+    let start = suggestion.span.start.min(end);
+
+    line_buffer.replace(start..end, &suggestion.value);
+    line_buffer.set_insertion_point(start + suggestion.value.len());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Span;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    // Only the part of the word before the cursor is declared
+    #[case("git chec|kout", 4, 8, "checkout", "git checkout|kout")]
+    // The whole word around the cursor is declared
+    #[case("git chec|kout", 4, 12, "checkout", "git checkout|")]
+    // The value is shorter than the span
+    #[case("cd somewhere/lo|ng", 3, 17, "x", "cd x|")]
+    // The span reaches past the buffer
+    #[case("ls fo|", 3, 10, "foo", "ls foo|")]
+    fn replacement_is_limited_to_span(
+        #[case] buffer: &str,
+        #[case] start: usize,
+        #[case] end: usize,
+        #[case] value: &str,
+        #[case] expected: &str,
+    ) {
+        let mut line_buffer = LineBuffer::new();
+        line_buffer.insert_str(&buffer.replace('|', ""));
+        line_buffer.set_insertion_point(buffer.find('|').unwrap());
+        let suggestion = Suggestion {
+            value: value.into(),
+            description: None,
+            span: Span::new(start, end),
+        };
+
+        replace_in_buffer(&suggestion, &mut line_buffer);
+
+        let cursor = line_buffer.insertion_point();
+        let buffer = line_buffer.get_buffer();
+        assert_eq!(
+            format!("{}|{}", &buffer[..cursor], &buffer[cursor..]),
+            expected
+        );
+    }
 
     #[test]
     fn parse_row_test() {
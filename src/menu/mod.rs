@@ -2,9 +2,9 @@ mod completion_menu;
 mod history_menu;
 pub mod menu_functions;
 
-use crate::{painting::Painter, Completer, History, LineBuffer, Suggestion};
+use crate::{painting::Painter, Completer, History, LineBuffer, PromptHistorySearch, Suggestion};
 pub use completion_menu::CompletionMenu;
-pub use history_menu::HistoryMenu;
+pub use history_menu::{HistoryMenu, HistorySearchMode};
 use nu_ansi_term::{Color, Style};
 
 /// Struct to store the menu style
@@ -27,6 +27,23 @@ impl Default for MenuTextStyle {
     }
 }
 
+/// Struct to store the style of the history reverse search
+pub struct HistorySearchStyle {
+    /// Text style for the parts of the found entry that match the search
+    pub match_style: Style,
+    /// Text style for the rest of the found entry
+    pub text_style: Style,
+}
+
+impl Default for HistorySearchStyle {
+    fn default() -> Self {
+        Self {
+            match_style: Color::Green.normal(),
+            text_style: Style::default(),
+        }
+    }
+}
+
 /// Defines all possible events that could happen with a menu.
 #[derive(Clone)]
 pub enum MenuEvent {
@@ -54,6 +71,8 @@ pub enum MenuEvent {
     NextPage,
     /// Move to previous page
     PreviousPage,
+    /// Select or unselect the current value in menus allowing several selected values
+    ToggleSelection,
 }
 
 /// Trait that defines how a menu will be printed by the painter
@@ -124,6 +143,18 @@ pub trait Menu: Send {
 
     /// Gets cached values from menu that will be displayed
     fn get_values(&self) -> &[Suggestion];
+
+    /// Search currently performed by the menu. When present, the prompt renders it
+    /// with [`crate::Prompt::render_prompt_history_search_indicator`] instead of
+    /// using the menu indicator
+    fn history_search(&self) -> Option<PromptHistorySearch> {
+        None
+    }
+
+    /// Several values can be selected with [`MenuEvent::ToggleSelection`]
+    fn multi_select(&self) -> bool {
+        false
+    }
 }
 
 pub(crate) enum ReedlineMenu {
@@ -161,6 +192,14 @@ impl Menu for ReedlineMenu {
         self.as_ref().indicator()
     }
 
+    fn history_search(&self) -> Option<PromptHistorySearch> {
+        self.as_ref().history_search()
+    }
+
+    fn multi_select(&self) -> bool {
+        self.as_ref().multi_select()
+    }
+
     fn is_active(&self) -> bool {
         self.as_ref().is_active()
     }
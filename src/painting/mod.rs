@@ -1,9 +1,13 @@
 mod painter;
 mod prompt_lines;
+mod repaint_throttle;
 mod styled_text;
 mod utils;
 
 pub use painter::Painter;
 pub(crate) use prompt_lines::PromptLines;
+pub(crate) use repaint_throttle::RepaintThrottle;
 pub use styled_text::StyledText;
-pub(crate) use utils::estimate_single_line_wraps;
+pub(crate) use utils::{estimate_single_line_wraps, line_width};
+#[cfg(test)]
+pub(crate) use utils::{styled, SharedBuffer};
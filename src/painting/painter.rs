@@ -6,12 +6,12 @@ use {
         Prompt,
     },
     crossterm::{
-        cursor::{self, MoveTo, RestorePosition, SavePosition},
+        cursor::{self, CursorShape, MoveTo, RestorePosition, SavePosition, SetCursorShape},
         style::{Print, ResetColor, SetForegroundColor},
         terminal::{self, Clear, ClearType, ScrollUp},
         QueueableCommand, Result,
     },
-    std::io::Write,
+    std::{borrow::Cow, io::Write},
 };
 
 // Returns a string that skips N number of lines with the next offset of lines
@@ -42,27 +42,79 @@ fn skip_buffer_lines(string: &str, skip: usize, offset: Option<usize>) -> &str {
     string[index..limit].trim_end_matches('\n')
 }
 
+// Indicator printed after the left prompt. An active menu replaces the prompt indicator
+// with its own one, or with the prompt's history search indicator while it is searching
+fn prompt_indicator<'a>(
+    prompt: &dyn Prompt,
+    lines: &'a PromptLines,
+    menu: Option<&'a ReedlineMenu>,
+) -> Cow<'a, str> {
+    match menu {
+        Some(menu) => match menu.history_search() {
+            Some(history_search) => Cow::Owned(
+                prompt
+                    .render_prompt_history_search_indicator(history_search)
+                    .into_owned(),
+            ),
+            None => Cow::Borrowed(menu.indicator()),
+        },
+        None => Cow::Borrowed(&lines.prompt_indicator),
+    }
+}
+
+// Printing enough new lines keeps the screen content in the scrollback, unlike
+// clearing it with an escape sequence
+fn clear_screen_sequence(screen_height: u16) -> String {
+    format!(
+        "{}{}{}{}",
+        cursor::Hide,
+        "\n".repeat(2 * screen_height as usize),
+        MoveTo(0, 0),
+        cursor::Show
+    )
+}
+
+fn clear_scrollback_sequence() -> String {
+    format!(
+        "{}{}{}",
+        Clear(ClearType::Purge),
+        Clear(ClearType::All),
+        MoveTo(0, 0)
+    )
+}
+
+// Resets the cursor to the shape configured in the terminal (DECSCUSR 0), which
+// crossterm's `CursorShape` has no variant for
+const DEFAULT_CURSOR_SHAPE: &str = "\x1b[0 q";
+
 /// the type used by crossterm operations
 pub type W = std::io::BufWriter<std::io::Stderr>;
 
 /// Implementation of the output to the terminal
 pub struct Painter {
     // Stdout
-    stdout: W,
+    stdout: Box<dyn Write + Send>,
     prompt_start_row: u16,
     terminal_size: (u16, u16),
     last_required_lines: u16,
     large_buffer: bool,
+    cursor_shape: Option<CursorShape>,
 }
 
 impl Painter {
     pub(crate) fn new(stdout: W) -> Self {
+        Self::from_writer(stdout)
+    }
+
+    /// Painter writing to any output, e.g. a buffer capturing the painted sequences
+    pub(crate) fn from_writer(stdout: impl Write + Send + 'static) -> Self {
         Painter {
-            stdout,
+            stdout: Box::new(stdout),
             prompt_start_row: 0,
             terminal_size: (0, 0),
             last_required_lines: 0,
             large_buffer: false,
+            cursor_shape: None,
         }
     }
 
@@ -78,7 +130,7 @@ impl Painter {
 
     /// Returns the available lines from the prompt down
     pub fn remaining_lines(&self) -> u16 {
-        self.screen_height() - self.prompt_start_row
+        self.screen_height().saturating_sub(self.prompt_start_row)
     }
 
     /// Check if the currently painted content exceeds the size of the screen
@@ -112,6 +164,8 @@ impl Painter {
             new_row
         };
         self.prompt_start_row = new_row;
+        // The hosting application may have changed the cursor in between
+        self.cursor_shape = None;
         Ok(())
     }
 
@@ -129,6 +183,7 @@ impl Painter {
         prompt: &dyn Prompt,
         lines: &PromptLines,
         menu: Option<&ReedlineMenu>,
+        cursor_shape: Option<CursorShape>,
         use_ansi_coloring: bool,
     ) -> Result<()> {
         self.stdout.queue(cursor::Hide)?;
@@ -170,17 +225,34 @@ impl Painter {
 
         self.stdout.queue(RestorePosition)?.queue(cursor::Show)?;
 
+        // Only emit the shape when the edit mode requires a different one
+        if let Some(shape) = cursor_shape {
+            if self.cursor_shape != Some(shape) {
+                self.stdout.queue(SetCursorShape(shape))?;
+                self.cursor_shape = Some(shape);
+            }
+        }
+
         self.stdout.flush()
     }
 
+    /// Restores the terminal's default cursor shape if the edit mode changed it
+    pub(crate) fn reset_cursor_shape(&mut self) -> Result<()> {
+        if self.cursor_shape.take().is_some() {
+            self.stdout.queue(Print(DEFAULT_CURSOR_SHAPE))?.flush()?;
+        }
+
+        Ok(())
+    }
+
     fn print_right_prompt(&mut self, lines: &PromptLines) -> Result<()> {
-        let prompt_length_right = line_width(&lines.prompt_str_right);
-        let start_position = self
-            .screen_width()
-            .saturating_sub(prompt_length_right as u16);
-        let input_width = lines.estimate_first_input_line_width();
+        let start_position = right_prompt_column(
+            self.screen_width(),
+            line_width(&lines.prompt_str_right),
+            lines.estimate_first_input_line_width(),
+        );
 
-        if input_width <= start_position {
+        if let Some(start_position) = start_position {
             self.stdout
                 .queue(SavePosition)?
                 .queue(cursor::MoveTo(start_position, self.prompt_start_row))?
@@ -235,11 +307,8 @@ impl Painter {
         self.stdout
             .queue(Print(&coerce_crlf(&lines.prompt_str_left)))?;
 
-        let prompt_indicator = match menu {
-            Some(menu) => menu.indicator(),
-            None => &lines.prompt_indicator,
-        };
-        self.stdout.queue(Print(&coerce_crlf(prompt_indicator)))?;
+        let prompt_indicator = prompt_indicator(prompt, lines, menu);
+        self.stdout.queue(Print(&coerce_crlf(&prompt_indicator)))?;
 
         self.print_right_prompt(lines)?;
 
@@ -278,10 +347,7 @@ impl Painter {
         // indicator is printed in the same line as the first line of the buffer
         let prompt_lines = lines.prompt_lines_with_wrap(screen_width) as usize;
 
-        let prompt_indicator = match menu {
-            Some(menu) => menu.indicator(),
-            None => &lines.prompt_indicator,
-        };
+        let prompt_indicator = prompt_indicator(prompt, lines, menu);
 
         let prompt_indicator_lines = prompt_indicator.lines().count();
         let before_cursor_lines = lines.before_cursor.lines().count();
@@ -308,7 +374,7 @@ impl Painter {
         // Adjusting extra_rows base on the calculated prompt line size
         let extra_rows = extra_rows.saturating_sub(prompt_lines);
 
-        let indicator_skipped = skip_buffer_lines(prompt_indicator, extra_rows, None);
+        let indicator_skipped = skip_buffer_lines(&prompt_indicator, extra_rows, None);
         self.stdout.queue(Print(&coerce_crlf(indicator_skipped)))?;
 
         if use_ansi_coloring {
@@ -364,15 +430,16 @@ impl Painter {
         let prev_prompt_row = self.prompt_start_row;
 
         self.terminal_size = (width, height);
-        // TODO properly adjusting prompt_origin on resizing while lines > 1
+        // A changed width is handled by the following repaint, which wraps the lines
+        // for the new width and scrolls up if they no longer fit below the prompt
 
-        if prev_prompt_row >= (height - 1) {
+        if prev_prompt_row >= height.saturating_sub(1) {
             // Terminal is shrinking up
             // FIXME: use actual prompt size at some point
             // Note: you can't just subtract the offset from the origin,
             // as we could be shrinking so fast that the offset we read back from
             // crossterm is past where it would have been.
-            self.prompt_start_row = height - 2;
+            self.prompt_start_row = height.saturating_sub(2);
         } else if prev_terminal_size.1 < height {
             // Terminal is growing down, so move the prompt down the same amount to make space
             // for history that's on the screen
@@ -400,16 +467,26 @@ impl Painter {
 
     /// Clear the screen by printing enough whitespace to start the prompt or
     /// other output back at the first line of the terminal.
+    ///
+    /// The visible content is kept in the scrollback.
     pub(crate) fn clear_screen(&mut self) -> Result<()> {
-        self.stdout.queue(cursor::Hide)?;
         let (_, num_lines) = terminal::size()?;
-        for _ in 0..2 * num_lines {
-            self.stdout.queue(Print("\n"))?;
-        }
-        self.stdout.queue(MoveTo(0, 0))?;
-        self.stdout.queue(cursor::Show)?;
+        self.stdout
+            .queue(Print(clear_screen_sequence(num_lines)))?
+            .flush()?;
+        self.prompt_start_row = 0;
 
-        self.stdout.flush()
+        Ok(())
+    }
+
+    /// Clears the screen including the scrollback and moves the prompt to the first line
+    pub(crate) fn clear_scrollback(&mut self) -> Result<()> {
+        self.stdout
+            .queue(Print(clear_scrollback_sequence()))?
+            .flush()?;
+        self.prompt_start_row = 0;
+
+        Ok(())
     }
 
     // The prompt is moved to the end of the buffer after the event was handled
@@ -428,11 +505,164 @@ impl Painter {
     }
 }
 
+/// Column where the right prompt starts, if it fits next to the input
+///
+/// The right prompt is hidden as soon as the input (including the cursor behind it)
+/// would reach into it. All widths are measured in terminal cells.
+fn right_prompt_column(screen_width: u16, prompt_width: usize, input_width: u16) -> Option<u16> {
+    let prompt_width = u16::try_from(prompt_width).ok()?;
+    let start_position = screen_width.checked_sub(prompt_width)?;
+
+    if input_width < start_position {
+        Some(start_position)
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{painting::SharedBuffer, PromptEditMode, PromptHistorySearch};
     use pretty_assertions::assert_eq;
 
+    struct TestPrompt;
+
+    impl Prompt for TestPrompt {
+        fn render_prompt_left(&self) -> Cow<'_, str> {
+            Cow::Borrowed("")
+        }
+
+        fn render_prompt_right(&self) -> Cow<'_, str> {
+            Cow::Borrowed("")
+        }
+
+        fn render_prompt_indicator(&self, _prompt_mode: PromptEditMode) -> Cow<'_, str> {
+            Cow::Borrowed("> ")
+        }
+
+        fn render_prompt_multiline_indicator(&self) -> Cow<'_, str> {
+            Cow::Borrowed("::: ")
+        }
+
+        fn render_prompt_history_search_indicator(
+            &self,
+            _history_search: PromptHistorySearch,
+        ) -> Cow<'_, str> {
+            Cow::Borrowed("? ")
+        }
+    }
+
+    #[test]
+    fn resize_rewraps_buffer_for_new_width() {
+        let output = SharedBuffer::default();
+        let mut painter = Painter::from_writer(output.clone());
+        painter.terminal_size = (20, 5);
+        painter.prompt_start_row = 4;
+
+        let lines = PromptLines::new(
+            &TestPrompt,
+            PromptEditMode::Default,
+            None,
+            "abcdefghijklm",
+            "nopqrstuvwxyz",
+            "",
+        );
+        painter
+            .repaint_buffer(&TestPrompt, &lines, None, None, false)
+            .unwrap();
+        // 28 columns need two rows at the bottom of the screen
+        assert_eq!(painter.prompt_start_row, 3);
+        output.take();
+
+        painter.handle_resize(10, 5);
+        painter
+            .repaint_buffer(&TestPrompt, &lines, None, None, false)
+            .unwrap();
+
+        // Three rows are needed now, the cursor is restored between both halves of the buffer
+        assert_eq!(painter.prompt_start_row, 2);
+        assert_eq!(
+            output.take(),
+            "\x1b[?25l\x1b[1S\x1b[3;1H\x1b[J> abcdefghijklm\x1b7nopqrstuvwxyz\x1b8\x1b[?25h"
+        );
+    }
+
+    #[test]
+    fn truncated_hint_fits_terminal_width() {
+        use crate::{DefaultHinter, FileBackedHistory, Hinter, History};
+
+        let output = SharedBuffer::default();
+        let mut painter = Painter::from_writer(output.clone());
+        painter.terminal_size = (20, 5);
+        painter.prompt_start_row = 4;
+
+        let mut history = FileBackedHistory::default();
+        history.append(&format!("ls {}", "\u{65e5}\u{672c}\u{8a9e}".repeat(10)));
+        let mut hinter = DefaultHinter::default().with_max_length(15);
+        let hint = hinter.handle("ls", 2, &history, false);
+
+        let lines = PromptLines::new(&TestPrompt, PromptEditMode::Default, None, "ls", "", &hint);
+        painter
+            .repaint_buffer(&TestPrompt, &lines, None, None, false)
+            .unwrap();
+
+        // 30 wide characters would need four rows, the truncated hint fits on the last one
+        assert_eq!(painter.prompt_start_row, 4);
+        assert_eq!(
+            output.take(),
+            "\x1b[?25l\x1b[5;1H\x1b[J> \x1b7\x1b[5;21H\x1b8ls\x1b7 \u{65e5}\u{672c}\u{8a9e}\u{65e5}\u{672c}\u{8a9e}\u{2026}\x1b8\x1b[?25h"
+        );
+    }
+
+    #[test]
+    fn resize_to_tiny_terminal_does_not_underflow() {
+        let mut painter = Painter::from_writer(SharedBuffer::default());
+        painter.terminal_size = (20, 5);
+        painter.prompt_start_row = 4;
+
+        painter.handle_resize(20, 1);
+
+        assert_eq!(painter.prompt_start_row, 0);
+        assert_eq!(painter.remaining_lines(), 1);
+    }
+
+    #[test]
+    fn clear_screen_keeps_scrollback() {
+        assert_eq!(
+            clear_screen_sequence(2),
+            "\x1b[?25l\n\n\n\n\x1b[1;1H\x1b[?25h"
+        );
+    }
+
+    #[test]
+    fn clear_scrollback_purges_history() {
+        assert_eq!(clear_scrollback_sequence(), "\x1b[3J\x1b[2J\x1b[1;1H");
+    }
+
+    #[test]
+    fn right_prompt_is_aligned_to_the_right_edge() {
+        assert_eq!(right_prompt_column(80, 10, 20), Some(70));
+        assert_eq!(right_prompt_column(80, 10, 69), Some(70));
+    }
+
+    #[test]
+    fn right_prompt_disappears_when_line_is_too_long() {
+        assert_eq!(right_prompt_column(80, 10, 70), None);
+        assert_eq!(right_prompt_column(80, 10, 75), None);
+        assert_eq!(right_prompt_column(8, 10, 0), None);
+    }
+
+    #[test]
+    fn right_prompt_accounts_for_wide_characters() {
+        let prompt_width = line_width("\u{65e5}\u{672c}");
+        let input_width = line_width("\u{8a9e}\u{8a9e}\u{8a9e}") as u16;
+
+        assert_eq!(prompt_width, 4);
+        assert_eq!(right_prompt_column(12, prompt_width, input_width), Some(8));
+        assert_eq!(right_prompt_column(10, prompt_width, input_width), None);
+    }
+
     #[test]
     fn test_skip_lines() {
         let string = "sentence1\nsentence2\nsentence3\n";
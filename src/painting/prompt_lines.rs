@@ -50,6 +50,22 @@ impl<'prompt> PromptLines<'prompt> {
         }
     }
 
+    /// Lines for an accepted input shown with the transient prompt of `prompt`
+    ///
+    /// Returns `None` if the prompt doesn't provide a transient version.
+    pub fn transient(prompt: &'prompt dyn Prompt, input: &'prompt str) -> Option<Self> {
+        let prompt_str_left = prompt.render_prompt_transient()?;
+
+        Some(Self {
+            prompt_str_left,
+            prompt_str_right: Cow::Borrowed(""),
+            prompt_indicator: Cow::Borrowed(""),
+            before_cursor: coerce_crlf(input),
+            after_cursor: Cow::Borrowed(""),
+            hint: Cow::Borrowed(""),
+        })
+    }
+
     /// The required lines to paint the buffer are calculated by counting the
     /// number of newlines in all the strings that form the prompt and buffer.
     /// The plus 1 is to indicate that there should be at least one line.
@@ -117,3 +133,14 @@ impl<'prompt> PromptLines<'prompt> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DefaultPrompt;
+
+    #[test]
+    fn prompt_without_transient_version_keeps_full_prompt() {
+        assert!(PromptLines::transient(&DefaultPrompt::default(), "ls -la").is_none());
+    }
+}
@@ -0,0 +1,97 @@
+use std::time::{Duration, Instant};
+
+/// Limits how often the read loop repaints by coalescing rapid edits into one paint
+///
+/// A repaint requested less than the interval after the last one is skipped and
+/// remembered, so it can be done once no more input arrives.
+#[derive(Debug, Default)]
+pub(crate) struct RepaintThrottle {
+    interval: Duration,
+    last_paint: Option<Instant>,
+    pending: bool,
+}
+
+impl RepaintThrottle {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            ..Self::default()
+        }
+    }
+
+    /// Returns if a requested repaint should be done now, otherwise it is remembered as pending
+    pub fn request(&mut self, now: Instant) -> bool {
+        match self.last_paint {
+            Some(last_paint) if now.saturating_duration_since(last_paint) < self.interval => {
+                self.pending = true;
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Records a repaint, which includes all the pending changes
+    pub fn painted(&mut self, now: Instant) {
+        self.last_paint = Some(now);
+        self.pending = false;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Time until the pending repaint is due, `None` if there is nothing to repaint
+    pub fn pending_wait(&self, now: Instant) -> Option<Duration> {
+        if !self.pending {
+            return None;
+        }
+
+        Some(self.last_paint.map_or(Duration::ZERO, |last_paint| {
+            self.interval
+                .saturating_sub(now.saturating_duration_since(last_paint))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn burst_of_requests_is_coalesced() {
+        let mut throttle = RepaintThrottle::new(Duration::from_millis(16));
+        let start = Instant::now();
+        let mut paints = 0;
+
+        for i in 0..10 {
+            let now = start + Duration::from_millis(i * 3);
+            if throttle.request(now) {
+                throttle.painted(now);
+                paints += 1;
+            }
+        }
+
+        // Painted at 0ms and 18ms, the requests after that wait for the interval
+        assert_eq!(paints, 2);
+        assert_eq!(
+            throttle.pending_wait(start + Duration::from_millis(27)),
+            Some(Duration::from_millis(7))
+        );
+        assert_eq!(
+            throttle.pending_wait(start + Duration::from_millis(40)),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn zero_interval_paints_every_request() {
+        let mut throttle = RepaintThrottle::default();
+        let now = Instant::now();
+
+        throttle.painted(now);
+        assert!(throttle.request(now));
+        assert!(!throttle.is_pending());
+        assert_eq!(throttle.pending_wait(now), None);
+    }
+}
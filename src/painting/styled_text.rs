@@ -2,6 +2,7 @@ use super::utils::strip_ansi;
 use nu_ansi_term::{Color, Style};
 
 /// A representation of a buffer with styling, used for doing syntax highlighting
+#[derive(Debug, Clone, PartialEq)]
 pub struct StyledText {
     /// The component, styled parts of the text
     pub buffer: Vec<(Style, String)>,
@@ -36,32 +37,51 @@ impl StyledText {
         insertion_point: usize,
         multiline_prompt: &str,
         use_ansi_coloring: bool,
+    ) -> (String, String) {
+        let line_count = self.raw_string().matches('\n').count();
+        let multiline_prompts = vec![multiline_prompt; line_count];
+
+        self.render_around_insertion_point_with_prompts(
+            insertion_point,
+            &multiline_prompts,
+            use_ansi_coloring,
+        )
+    }
+
+    /// Same as [`StyledText::render_around_insertion_point`] but with an individual
+    /// continuation prompt for every line after the first one
+    ///
+    /// `multiline_prompts[0]` is inserted in front of the second line and so on.
+    /// Missing prompts are left empty.
+    pub fn render_around_insertion_point_with_prompts(
+        &self,
+        insertion_point: usize,
+        multiline_prompts: &[&str],
+        use_ansi_coloring: bool,
     ) -> (String, String) {
         let mut current_idx = 0;
+        let mut current_line = 0;
         let mut left_string = String::new();
         let mut right_string = String::new();
         let prompt_style = Style::new().fg(Color::LightBlue);
+        let mut render = |pair: &(Style, String)| {
+            let rendered = render_as_string(pair, &prompt_style, multiline_prompts, current_line);
+            current_line += pair.1.matches('\n').count();
+            rendered
+        };
         for pair in &self.buffer {
             if current_idx >= insertion_point {
-                right_string.push_str(&render_as_string(pair, &prompt_style, multiline_prompt));
+                right_string.push_str(&render(pair));
             } else if pair.1.len() + current_idx <= insertion_point {
-                left_string.push_str(&render_as_string(pair, &prompt_style, multiline_prompt));
+                left_string.push_str(&render(pair));
             } else if pair.1.len() + current_idx > insertion_point {
                 let offset = insertion_point - current_idx;
 
                 let left_side = pair.1[..offset].to_string();
                 let right_side = pair.1[offset..].to_string();
 
-                left_string.push_str(&render_as_string(
-                    &(pair.0, left_side),
-                    &prompt_style,
-                    multiline_prompt,
-                ));
-                right_string.push_str(&render_as_string(
-                    &(pair.0, right_side),
-                    &prompt_style,
-                    multiline_prompt,
-                ));
+                left_string.push_str(&render(&(pair.0, left_side)));
+                right_string.push_str(&render(&(pair.0, right_side)));
             }
             current_idx += pair.1.len();
         }
@@ -90,15 +110,39 @@ impl StyledText {
 fn render_as_string(
     renderable: &(Style, String),
     prompt_style: &Style,
-    multiline_prompt: &str,
+    multiline_prompts: &[&str],
+    first_line: usize,
 ) -> String {
     let mut rendered = String::new();
-    let formatted_multiline_prompt = format!("\n{}", multiline_prompt);
     for (line_number, line) in renderable.1.split('\n').enumerate() {
         if line_number != 0 {
+            let multiline_prompt = multiline_prompts
+                .get(first_line + line_number - 1)
+                .copied()
+                .unwrap_or_default();
+            let formatted_multiline_prompt = format!("\n{}", multiline_prompt);
             rendered.push_str(&prompt_style.paint(&formatted_multiline_prompt).to_string());
         }
         rendered.push_str(&renderable.0.paint(line).to_string());
     }
     rendered
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn every_line_gets_its_own_prompt() {
+        let mut styled_text = StyledText::new();
+        styled_text.push((Style::new(), "a {\n'b".to_string()));
+        styled_text.push((Style::new(), "\nc".to_string()));
+
+        let (left, right) =
+            styled_text.render_around_insertion_point_with_prompts(6, &["... ", "quote> "], false);
+
+        assert_eq!(left, "a {\n... 'b");
+        assert_eq!(right, "\nquote> c");
+    }
+}
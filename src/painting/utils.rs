@@ -71,6 +71,41 @@ pub(crate) fn line_width(line: &str) -> usize {
     strip_ansi(line).width()
 }
 
+/// Writer keeping the painted output for tests
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub(crate) struct SharedBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+#[cfg(test)]
+impl SharedBuffer {
+    /// Takes the output written since the last call
+    pub(crate) fn take(&self) -> String {
+        String::from_utf8(std::mem::take(&mut *self.0.lock().unwrap())).unwrap()
+    }
+}
+
+#[cfg(test)]
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Builds a [`StyledText`](super::StyledText) from its styled parts for tests
+#[cfg(test)]
+pub(crate) fn styled(parts: &[(nu_ansi_term::Style, &str)]) -> super::StyledText {
+    super::StyledText {
+        buffer: parts
+            .iter()
+            .map(|(style, text)| (*style, text.to_string()))
+            .collect(),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -99,6 +99,13 @@ pub trait Prompt: Send {
         &self,
         history_search: PromptHistorySearch,
     ) -> Cow<str>;
+    /// Compact prompt that replaces the full prompt (including the indicator) once the line
+    /// has been accepted, so only this version stays in the scrollback.
+    ///
+    /// Returns `None` by default to keep the full prompt.
+    fn render_prompt_transient(&self) -> Option<Cow<'_, str>> {
+        None
+    }
     /// Get back the prompt color
     fn get_prompt_color(&self) -> Color {
         DEFAULT_PROMPT_COLOR
@@ -1,5 +1,6 @@
 mod query;
 pub(crate) mod text_manipulation;
+pub(crate) mod utf8_decoder;
 
 pub use query::{
     get_reedline_default_keybindings, get_reedline_edit_commands,
@@ -1,3 +1,4 @@
+use std::sync::atomic::{compiler_fence, Ordering};
 use unicode_segmentation::UnicodeSegmentation;
 
 pub fn remove_last_grapheme(string: &str) -> &str {
@@ -10,6 +11,17 @@ pub fn remove_last_grapheme(string: &str) -> &str {
     }
 }
 
+/// Overwrites the string with zeros before clearing it, e.g. after reading a password
+///
+/// The fence keeps the compiler from dropping the writes as the string is cleared anyway.
+pub(crate) fn zeroize(text: &mut String) {
+    let zeros = "\0".repeat(text.len());
+    // Replacing with a string of the same length writes in place without reallocating
+    text.replace_range(.., &zeros);
+    compiler_fence(Ordering::SeqCst);
+    text.clear();
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -35,4 +47,15 @@ mod test {
 
         assert_eq!(remove_last_grapheme(string), "this is a 😞");
     }
+
+    #[test]
+    fn zeroize_overwrites_in_place() {
+        let mut string = "pässwort".to_string();
+        let buffer = string.as_ptr();
+
+        zeroize(&mut string);
+
+        assert_eq!(string, "");
+        assert_eq!(string.as_ptr(), buffer);
+    }
 }
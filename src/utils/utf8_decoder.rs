@@ -0,0 +1,134 @@
+use std::io::{self, ErrorKind, Read};
+
+/// Decodes UTF-8 that arrives in chunks, e.g. from several reads
+///
+/// A multi-byte sequence split across chunks is kept until it is complete.
+/// Invalid bytes are replaced with U+FFFD, so the text is always valid and
+/// the cursor positions in it stay on char boundaries.
+#[derive(Debug, Default)]
+pub(crate) struct Utf8Decoder {
+    incomplete: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    /// Decodes the complete chars of the bytes received so far
+    pub fn decode(&mut self, bytes: &[u8]) -> String {
+        self.incomplete.extend_from_slice(bytes);
+
+        let mut decoded = String::new();
+        let mut rest = self.incomplete.as_slice();
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    decoded.push_str(valid);
+                    rest = &[];
+                    break;
+                }
+                Err(error) => {
+                    let (valid, after_valid) = rest.split_at(error.valid_up_to());
+                    // The slice was just validated up to this point
+                    decoded.push_str(std::str::from_utf8(valid).unwrap_or_default());
+
+                    match error.error_len() {
+                        Some(invalid_len) => {
+                            decoded.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after_valid[invalid_len..];
+                        }
+                        // The sequence at the end may be completed by the next bytes
+                        None => {
+                            rest = after_valid;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.incomplete = rest.to_vec();
+        decoded
+    }
+
+    /// Ends the input, replacing a sequence that was never completed
+    pub fn finish(&mut self) -> String {
+        if self.incomplete.is_empty() {
+            String::new()
+        } else {
+            self.incomplete.clear();
+            char::REPLACEMENT_CHARACTER.to_string()
+        }
+    }
+}
+
+/// Reads all of `reader`, replacing invalid UTF-8 instead of failing
+pub(crate) fn read_to_string_lossy(mut reader: impl Read) -> io::Result<String> {
+    let mut decoder = Utf8Decoder::default();
+    let mut text = String::new();
+    let mut chunk = [0; 8192];
+
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => text.push_str(&decoder.decode(&chunk[..read])),
+            Err(error) if error.kind() == ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+    text.push_str(&decoder.finish());
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::LineBuffer;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn split_sequence_is_completed_by_next_read() {
+        let mut decoder = Utf8Decoder::default();
+        let bytes = "aä€".as_bytes();
+
+        assert_eq!(decoder.decode(&bytes[..2]), "a");
+        assert_eq!(decoder.decode(&bytes[2..4]), "ä");
+        assert_eq!(decoder.decode(&bytes[4..]), "€");
+        assert_eq!(decoder.finish(), "");
+    }
+
+    #[test]
+    fn invalid_bytes_are_replaced() {
+        let mut decoder = Utf8Decoder::default();
+
+        assert_eq!(decoder.decode(b"a\xffb\xe2\x82"), "a\u{fffd}b");
+        // The started sequence is broken by the next byte
+        assert_eq!(decoder.decode(b"c"), "\u{fffd}c");
+        assert_eq!(decoder.decode(b"\xf0\x9f"), "");
+        assert_eq!(decoder.finish(), "\u{fffd}");
+    }
+
+    #[test]
+    fn decoded_text_keeps_cursor_on_char_boundaries() {
+        let mut decoder = Utf8Decoder::default();
+        let mut line_buffer = LineBuffer::new();
+
+        for chunk in [&b"x\xc3"[..], b"\xa4\x80y", b"\xe2\x82\xac"] {
+            line_buffer.insert_str(&decoder.decode(chunk));
+        }
+        line_buffer.insert_str(&decoder.finish());
+        assert_eq!(line_buffer.get_buffer(), "xä\u{fffd}y€");
+
+        line_buffer.move_left();
+        line_buffer.move_left();
+        line_buffer.delete_left_grapheme();
+        assert_eq!(line_buffer.get_buffer(), "xäy€");
+        line_buffer.move_right();
+        line_buffer.insert_char('z');
+        assert_eq!(line_buffer.get_buffer(), "xäyz€");
+    }
+
+    #[test]
+    fn reads_all_chunks_lossily() {
+        let text = read_to_string_lossy(&b"ok \xe2\x82\xac \xfe"[..]).unwrap();
+        assert_eq!(text, "ok € \u{fffd}");
+    }
+}
@@ -6,6 +6,14 @@ pub use default::DefaultValidator;
 pub trait Validator: Send {
     /// The action that will handle the current buffer as a line and return the corresponding validation
     fn validate(&self, line: &str) -> ValidationResult;
+
+    /// Indicator shown in front of a continuation line, given the input before that line
+    ///
+    /// Allows to show why the input is incomplete (e.g. an open quote or an open bracket).
+    /// Returns `None` to show the [`crate::Prompt::render_prompt_multiline_indicator`]
+    fn continuation_indicator(&self, _previous_input: &str) -> Option<String> {
+        None
+    }
 }
 
 /// Whether or not the validation shows the input was complete